@@ -0,0 +1,11 @@
+/*
+  Warnings:
+
+  - A unique constraint covering the columns `[joinCode]` on the table `Project` will be added. If there are existing duplicate values, this will fail.
+
+*/
+-- AlterTable
+ALTER TABLE `Project` ADD COLUMN `joinCode` VARCHAR(191) NULL;
+
+-- CreateIndex
+CREATE UNIQUE INDEX `Project_joinCode_key` ON `Project`(`joinCode`);
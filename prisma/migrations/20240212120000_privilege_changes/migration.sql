@@ -0,0 +1,19 @@
+-- CreateTable
+CREATE TABLE `PrivilegeChange` (
+    `id` VARCHAR(191) NOT NULL,
+    `userId` VARCHAR(191) NOT NULL,
+    `changedById` VARCHAR(191) NOT NULL,
+    `previousPrivilege` ENUM('NONE', 'VOLUNTEER', 'TEAM', 'EXEC', 'TECH', 'FINANCE') NOT NULL,
+    `newPrivilege` ENUM('NONE', 'VOLUNTEER', 'TEAM', 'EXEC', 'TECH', 'FINANCE') NOT NULL,
+    `time` DATETIME(3) NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+
+    INDEX `PrivilegeChange_userId_idx`(`userId`),
+    INDEX `PrivilegeChange_changedById_idx`(`changedById`),
+    PRIMARY KEY (`id`)
+) DEFAULT CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+
+-- AddForeignKey
+ALTER TABLE `PrivilegeChange` ADD CONSTRAINT `PrivilegeChange_userId_fkey` FOREIGN KEY (`userId`) REFERENCES `User`(`id`) ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `PrivilegeChange` ADD CONSTRAINT `PrivilegeChange_changedById_fkey` FOREIGN KEY (`changedById`) REFERENCES `User`(`id`) ON DELETE RESTRICT ON UPDATE CASCADE;
@@ -0,0 +1,17 @@
+-- CreateTable
+CREATE TABLE `AuditLog` (
+    `id` VARCHAR(191) NOT NULL,
+    `actorId` VARCHAR(191) NOT NULL,
+    `actorRole` ENUM('NONE', 'VOLUNTEER', 'TEAM', 'EXEC', 'TECH', 'FINANCE') NOT NULL,
+    `action` ENUM('CREATE', 'UPDATE', 'DELETE') NOT NULL,
+    `entityType` VARCHAR(191) NOT NULL,
+    `entityId` VARCHAR(191) NOT NULL,
+    `before` JSON NULL,
+    `after` JSON NULL,
+    `time` DATETIME(3) NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+
+    INDEX `AuditLog_actorId_idx`(`actorId`),
+    INDEX `AuditLog_entityType_entityId_idx`(`entityType`, `entityId`),
+    INDEX `AuditLog_time_idx`(`time`),
+    PRIMARY KEY (`id`)
+) DEFAULT CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
@@ -0,0 +1,14 @@
+-- AlterTable
+ALTER TABLE `Event` ADD COLUMN `capacity` INTEGER NULL;
+
+-- AlterTable
+ALTER TABLE `Location` ADD COLUMN `capacity` INTEGER NULL;
+
+-- AlterTable
+ALTER TABLE `Scan` ADD COLUMN `type` ENUM('CHECK_IN', 'CHECK_OUT') NOT NULL DEFAULT 'CHECK_IN';
+
+-- CreateIndex
+CREATE UNIQUE INDEX `Scan_eventId_registrationId_type_key` ON `Scan`(`eventId`, `registrationId`, `type`);
+
+-- DropIndex
+DROP INDEX `Scan_eventId_registrationId_key` ON `Scan`;
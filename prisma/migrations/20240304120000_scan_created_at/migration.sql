@@ -0,0 +1,5 @@
+-- AlterTable
+-- The default is only set once the column exists, so scans recorded before this migration are left
+-- NULL instead of all getting the time it ran
+ALTER TABLE `Scan` ADD COLUMN `createdAt` DATETIME(3) NULL;
+ALTER TABLE `Scan` MODIFY `createdAt` DATETIME(3) NULL DEFAULT CURRENT_TIMESTAMP(3);
@@ -0,0 +1,8 @@
+-- AlterTable
+ALTER TABLE `Scan` ADD COLUMN `deviceId` VARCHAR(191) NULL,
+    ADD COLUMN `latitude` DOUBLE NULL,
+    ADD COLUMN `longitude` DOUBLE NULL,
+    ADD COLUMN `notes` VARCHAR(191) NULL;
+
+-- CreateIndex
+CREATE INDEX `Scan_hackathonId_createdAt_idx` ON `Scan`(`hackathonId`, `createdAt`);
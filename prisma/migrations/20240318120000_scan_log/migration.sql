@@ -0,0 +1,12 @@
+-- AlterTable
+-- Existing scans are given ids before the column becomes the primary key
+ALTER TABLE `Scan` ADD COLUMN `id` VARCHAR(191) NULL;
+UPDATE `Scan` SET `id` = UUID();
+ALTER TABLE `Scan` MODIFY `id` VARCHAR(191) NOT NULL,
+    ADD PRIMARY KEY (`id`);
+
+-- CreateIndex
+CREATE INDEX `Scan_eventId_registrationId_createdAt_idx` ON `Scan`(`eventId`, `registrationId`, `createdAt`);
+
+-- DropIndex
+DROP INDEX `Scan_eventId_registrationId_type_key` ON `Scan`;
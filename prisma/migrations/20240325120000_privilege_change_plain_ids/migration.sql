@@ -0,0 +1,5 @@
+-- DropForeignKey
+ALTER TABLE `PrivilegeChange` DROP FOREIGN KEY `PrivilegeChange_userId_fkey`;
+
+-- DropForeignKey
+ALTER TABLE `PrivilegeChange` DROP FOREIGN KEY `PrivilegeChange_changedById_fkey`;
@@ -0,0 +1,9 @@
+-- AlterTable
+ALTER TABLE `User` MODIFY `privilege` ENUM('NONE', 'VOLUNTEER', 'TEAM', 'EXEC', 'TECH', 'FINANCE', 'SPONSOR') NOT NULL DEFAULT 'NONE';
+
+-- AlterTable
+ALTER TABLE `PrivilegeChange` MODIFY `previousPrivilege` ENUM('NONE', 'VOLUNTEER', 'TEAM', 'EXEC', 'TECH', 'FINANCE', 'SPONSOR') NOT NULL,
+    MODIFY `newPrivilege` ENUM('NONE', 'VOLUNTEER', 'TEAM', 'EXEC', 'TECH', 'FINANCE', 'SPONSOR') NOT NULL;
+
+-- AlterTable
+ALTER TABLE `AuditLog` MODIFY `actorRole` ENUM('NONE', 'VOLUNTEER', 'TEAM', 'EXEC', 'TECH', 'FINANCE', 'SPONSOR') NOT NULL;
@@ -0,0 +1,9 @@
+-- AlterTable
+ALTER TABLE `Score` MODIFY `challenge1` INTEGER NULL,
+    MODIFY `challenge2` INTEGER NULL,
+    MODIFY `challenge3` INTEGER NULL;
+
+-- Until now a 0 meant the challenge wasn't entered
+UPDATE `Score` SET `challenge1` = NULL WHERE `challenge1` = 0;
+UPDATE `Score` SET `challenge2` = NULL WHERE `challenge2` = 0;
+UPDATE `Score` SET `challenge3` = NULL WHERE `challenge3` = 0;
@@ -0,0 +1,187 @@
+use axum::{
+    extract::Request,
+    http::HeaderValue,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use hyper::StatusCode;
+use prisma_client_rust::{
+    prisma_errors::query_engine::{RecordNotFound, UniqueKeyViolation},
+    QueryError,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// The error every handler returns. It's rendered as an [`ApiErrorBody`] with the matching status,
+/// and server-side failures are logged rather than sent to the client.
+#[derive(Debug)]
+pub enum ApiError {
+    BadRequest(String),
+    Unauthorized,
+    Forbidden(String),
+    NotFound(String),
+    Conflict(String),
+    PayloadTooLarge(String),
+    UnsupportedMediaType(String),
+    Validation(Vec<FieldError>),
+    BadGateway(String),
+    Internal(String),
+}
+
+/// The JSON body of every error response.
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiErrorBody {
+    /// Stable, machine readable reason, e.g. NOT_FOUND or VALIDATION_FAILED
+    pub code: String,
+    pub message: String,
+    /// Echoes the `x-request-id` of the request, to find it in the logs
+    pub request_id: Option<String>,
+    /// Only set for VALIDATION_FAILED
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub field_errors: Vec<FieldError>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldError {
+    /// Path of the invalid field in the request, e.g. `name` or `scores[2].value`
+    pub field: String,
+    /// The rule that failed, e.g. length or range
+    pub code: String,
+    pub message: Option<String>,
+}
+
+impl ApiError {
+    /// For errors that arrive as a status and message, like those of the storage backends.
+    pub fn from_status(status_code: StatusCode, message: String) -> Self {
+        match status_code {
+            StatusCode::BAD_REQUEST => Self::BadRequest(message),
+            StatusCode::UNAUTHORIZED => Self::Unauthorized,
+            StatusCode::FORBIDDEN => Self::Forbidden(message),
+            StatusCode::NOT_FOUND => Self::NotFound(message),
+            StatusCode::CONFLICT => Self::Conflict(message),
+            StatusCode::PAYLOAD_TOO_LARGE => Self::PayloadTooLarge(message),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE => Self::UnsupportedMediaType(message),
+            StatusCode::BAD_GATEWAY => Self::BadGateway(message),
+            _ => Self::Internal(message),
+        }
+    }
+
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::BadRequest(_) => StatusCode::BAD_REQUEST,
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::Forbidden(_) => StatusCode::FORBIDDEN,
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::Conflict(_) => StatusCode::CONFLICT,
+            Self::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Self::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::BadGateway(_) => StatusCode::BAD_GATEWAY,
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::BadRequest(_) => "BAD_REQUEST",
+            Self::Unauthorized => "UNAUTHORIZED",
+            Self::Forbidden(_) => "FORBIDDEN",
+            Self::NotFound(_) => "NOT_FOUND",
+            Self::Conflict(_) => "CONFLICT",
+            Self::PayloadTooLarge(_) => "PAYLOAD_TOO_LARGE",
+            Self::UnsupportedMediaType(_) => "UNSUPPORTED_MEDIA_TYPE",
+            Self::Validation(_) => "VALIDATION_FAILED",
+            Self::BadGateway(_) => "BAD_GATEWAY",
+            Self::Internal(_) => "INTERNAL",
+        }
+    }
+
+    /// The body sent to the client. Upstream and internal messages are replaced, as they can
+    /// contain database or provider details.
+    pub fn body(self, request_id: Option<String>) -> ApiErrorBody {
+        let code = self.code().to_owned();
+        let (message, field_errors) = match self {
+            Self::BadRequest(message)
+            | Self::Forbidden(message)
+            | Self::NotFound(message)
+            | Self::Conflict(message)
+            | Self::PayloadTooLarge(message)
+            | Self::UnsupportedMediaType(message) => (message, vec![]),
+            Self::Unauthorized => ("Unauthorized".to_owned(), vec![]),
+            Self::Validation(field_errors) => ("Validation failed".to_owned(), field_errors),
+            Self::BadGateway(_) => ("Upstream service failed".to_owned(), vec![]),
+            Self::Internal(_) => ("Internal server error".to_owned(), vec![]),
+        };
+        ApiErrorBody {
+            code,
+            message,
+            request_id,
+            field_errors,
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let request_id = current_request_id();
+        let status_code = self.status_code();
+        match &self {
+            Self::Internal(message) | Self::BadGateway(message) => {
+                tracing::error!(
+                    request_id = request_id.as_deref().unwrap_or("-"),
+                    status = %status_code,
+                    "{message}"
+                );
+            }
+            _ => {}
+        }
+        (status_code, Json(self.body(request_id))).into_response()
+    }
+}
+
+/// Unique-constraint violations become 409 and missing records 404. Anything else is a 500 whose
+/// details only go to the logs.
+impl From<QueryError> for ApiError {
+    fn from(err: QueryError) -> Self {
+        if err.is_prisma_error::<UniqueKeyViolation>() {
+            Self::Conflict("A record with the same unique fields already exists".to_owned())
+        } else if err.is_prisma_error::<RecordNotFound>() {
+            Self::NotFound("Record not found".to_owned())
+        } else {
+            Self::Internal(err.to_string())
+        }
+    }
+}
+
+/// The id of the request being handled, if called within [`request_id`].
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+/// Gives every request an id, taken from its `x-request-id` header or generated, that error bodies
+/// include and that's echoed back in the response header.
+pub async fn request_id(request: Request, next: Next) -> Response {
+    let id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty() && value.len() <= 128)
+        .map(str::to_owned)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let mut response = REQUEST_ID.scope(id.clone(), next.run(request)).await;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
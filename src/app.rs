@@ -1,7 +1,9 @@
 use std::time::Duration;
 
-use axum::{error_handling::HandleErrorLayer, http::HeaderValue, routing::get, BoxError, Router};
-use hyper::{Method, StatusCode};
+use axum::{
+    error_handling::HandleErrorLayer, http::HeaderValue, middleware, routing::get, BoxError, Router,
+};
+use hyper::Method;
 use socketioxide::SocketIo;
 use tower::{buffer::BufferLayer, limit::RateLimitLayer, ServiceBuilder};
 use tower_cookies::CookieManagerLayer;
@@ -11,10 +13,11 @@ use utoipa::OpenApi;
 use utoipa_redoc::{Redoc, Servable};
 
 use crate::{
+    api_error::{self, ApiError},
     base_types::{AppState, APP_STATE},
     docs::ApiDoc,
     routes,
-    socket::on_connect,
+    socket::{self, on_connect},
     utils,
 };
 
@@ -23,7 +26,7 @@ pub async fn new_app() -> Router {
     let cors_layer = create_cors_layer();
 
     let (socket_layer, io) = SocketIo::new_layer();
-    io.ns("/socket", on_connect);
+    io.ns(socket::NAMESPACE, on_connect);
 
     let app_state = AppState::new(io).await;
     APP_STATE.set(app_state.clone()).unwrap();
@@ -37,6 +40,7 @@ pub async fn new_app() -> Router {
         .merge(Redoc::with_url("/docs", ApiDoc::openapi()))
         .merge(service_layer)
         .merge(cors_layer)
+        .layer(middleware::from_fn(api_error::request_id))
         .layer(TraceLayer::new_for_http())
         .layer(CookieManagerLayer::new())
         .fallback(get(utils::handle_404))
@@ -66,6 +70,31 @@ fn setup_routes(app_state: AppState) -> Router {
             routes::events::events_get_router(app_state.clone()),
         )
         .nest("/scans", routes::scans::scans_get_router(app_state.clone()))
+        .nest(
+            "/registrations",
+            routes::registrations::registration_get_router(app_state.clone()),
+        )
+        .nest(
+            "/projects",
+            routes::projects::project_get_router(app_state.clone()),
+        )
+        .nest(
+            "/scores",
+            routes::scores::score_get_router(app_state.clone()),
+        )
+        .nest(
+            "/audit-logs",
+            routes::audit_logs::audit_log_get_router(app_state.clone()),
+        )
+        .nest(
+            "/metrics",
+            routes::metrics::metrics_get_router(app_state.clone()),
+        )
+        .nest(
+            "/organizers",
+            routes::organizers::organizer_get_router(app_state.clone()),
+        )
+        .nest("/teams", routes::teams::team_get_router(app_state.clone()))
         .nest("/users", routes::users::user_get_router(app_state))
 }
 
@@ -73,10 +102,7 @@ fn create_service_layer() -> Router {
     Router::new().layer(
         ServiceBuilder::new()
             .layer(HandleErrorLayer::new(|err: BoxError| async move {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    format!("Unhandled internal error: {err}"),
-                )
+                ApiError::Internal(format!("Unhandled internal error: {err}"))
             }))
             .layer(BufferLayer::new(1024))
             .layer(RateLimitLayer::new(100, Duration::from_secs(5))),
@@ -86,7 +112,12 @@ fn create_service_layer() -> Router {
 fn create_cors_layer() -> Router {
     Router::new().layer(
         CorsLayer::new()
-            .allow_methods(vec![Method::GET, Method::POST, Method::DELETE])
+            .allow_methods(vec![
+                Method::GET,
+                Method::POST,
+                Method::PATCH,
+                Method::DELETE,
+            ])
             .allow_origin("http://localhost:3000".parse::<HeaderValue>().unwrap())
             .allow_credentials(true),
     )
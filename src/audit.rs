@@ -0,0 +1,46 @@
+use serde::Serialize;
+
+use crate::{
+    auth_guard::RequestUser,
+    base_types::AppState,
+    prisma::{audit_log, AuditAction},
+};
+
+/// Serializes a row into the JSON stored in an audit entry's `before` or `after` column.
+pub fn snapshot<T: Serialize>(data: &T) -> Option<serde_json::Value> {
+    serde_json::to_value(data).ok()
+}
+
+/// Records that `actor` performed `action` on the entity `entity_type`/`entity_id`.
+///
+/// Called after the mutation has succeeded, so a failure to write the entry is logged rather than
+/// turned into an error for a request that has already taken effect.
+pub async fn record(
+    app_state: &AppState,
+    actor: &RequestUser,
+    action: AuditAction,
+    entity_type: &str,
+    entity_id: String,
+    before: Option<serde_json::Value>,
+    after: Option<serde_json::Value>,
+) {
+    if let Err(err) = app_state
+        .client
+        .audit_log()
+        .create(
+            actor.id.clone(),
+            actor.privilege,
+            action,
+            entity_type.to_owned(),
+            entity_id.clone(),
+            vec![audit_log::before::set(before), audit_log::after::set(after)],
+        )
+        .exec()
+        .await
+    {
+        tracing::error!(
+            "Failed to record audit entry for {action:?} on {entity_type} {entity_id} by {}: {err}",
+            actor.id
+        );
+    }
+}
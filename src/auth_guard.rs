@@ -1,32 +1,21 @@
-use std::future::Future;
-
-use axum::{extract::State, http::HeaderValue, middleware::Next, response::Response, Router};
+use std::{future::Future, marker::PhantomData};
+
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, State},
+    http::request::Parts,
+    middleware::Next,
+    response::Response,
+};
 use hyper::{HeaderMap, Request, StatusCode};
-use serde::{Deserialize, Serialize};
 
 use crate::{
-    base_types::{AppState, APP_STATE},
-    prisma::{user, Role},
+    api_error::ApiError,
+    auth_provider::{token_expiry, AuthIdentity},
+    base_types::AppState,
+    permissions::{Permission, PermissionTable, RequiredPermission},
+    prisma::user,
 };
-const FIREBASE_USER_DATA_ENDPOINT: &str =
-    "https://identitytoolkit.googleapis.com/v1/accounts:lookup";
-
-#[derive(Deserialize, Serialize, Debug, Clone, Default)]
-#[serde(rename_all = "camelCase")]
-pub struct FirebaseUserResponse {
-    local_id: String,
-    email: String,
-    email_verified: bool,
-    valid_since: String,
-    disabled: bool,
-    last_login_at: String,
-}
-
-#[derive(Deserialize, Serialize, Debug, Clone)]
-pub struct FirebaseUserResult {
-    kind: String,
-    users: Vec<FirebaseUserResponse>,
-}
 
 fn extract_auth_header(headers: &HeaderMap) -> Result<String, StatusCode> {
     let header = headers
@@ -41,132 +30,146 @@ fn extract_auth_header(headers: &HeaderMap) -> Result<String, StatusCode> {
         .ok_or(StatusCode::UNAUTHORIZED)
 }
 
-async fn fetch_firebase_user(
-    auth_header: &str,
-    app_state: &AppState,
-) -> Result<FirebaseUserResult, StatusCode> {
-    let user_data = app_state
-        .reqwest_client
-        .post(FIREBASE_USER_DATA_ENDPOINT)
-        .query(&[(
-            "key",
-            std::env::var("FIREBASE_API_KEY").map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
-        )])
-        .json(&serde_json::json!({ "idToken": auth_header }))
-        .send()
-        .await
-        .map_err(|_| StatusCode::UNAUTHORIZED)?;
-
-    if user_data.status() == reqwest::StatusCode::OK {
-        serde_json::from_str(
-            &user_data
-                .text()
-                .await
-                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
-        )
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
-    } else {
-        Err(StatusCode::UNAUTHORIZED)
+/// Looks up the user a bearer token belongs to with the configured auth provider, reusing the
+/// result for the same token until the cache entry or the token expires.
+async fn find_token_user(token: &str, app_state: &AppState) -> Result<user::Data, StatusCode> {
+    if let Some(user) = app_state.user_cache.get(token) {
+        return Ok(user);
     }
-}
 
-pub async fn require_auth(
-    State(app_state): State<AppState>,
-    headers: HeaderMap,
-    mut request: Request<axum::body::Body>,
-    next: Next,
-) -> Result<Response, StatusCode> {
-    let auth_header = extract_auth_header(&headers)?;
-    let firebase_user = fetch_firebase_user(&auth_header, &app_state).await?;
-
-    let user_uid = firebase_user
-        .users
-        .first()
-        .map(|user| user.local_id.clone())
-        .ok_or(StatusCode::UNAUTHORIZED)?;
+    let identity = app_state.auth_provider.authenticate(token).await?;
 
     let user = app_state
         .client
         .user()
-        .find_unique(user::UniqueWhereParam::GcpIdEquals(user_uid))
+        .find_unique(user::UniqueWhereParam::GcpIdEquals(identity.uid))
         .exec()
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
         .ok_or(StatusCode::UNAUTHORIZED)?;
 
+    // Tokens the provider accepted always have an `exp`, but without one it's safer not to cache
+    if let Some(expiry) = token_expiry(token) {
+        app_state.user_cache.insert(token, user.clone(), expiry);
+    }
+    Ok(user)
+}
+
+/// Anything but a 401 from verifying a token is our or the provider's fault, so it's reported as
+/// such rather than as the caller being unauthorized.
+fn auth_error(status_code: StatusCode) -> ApiError {
+    ApiError::from_status(status_code, "Couldn't verify the token".to_owned())
+}
+
+pub async fn require_auth(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    mut request: Request<axum::body::Body>,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let auth_header = extract_auth_header(&headers).map_err(auth_error)?;
+    let user = find_token_user(&auth_header, &app_state)
+        .await
+        .map_err(auth_error)?;
+
     request.extensions_mut().insert(user);
     Ok(next.run(request).await)
 }
 
-type Predicate = (Role, Box<dyn Fn(user::Data) -> bool>);
+/// Like [`require_auth`], but only verifies the token and passes on its [`AuthIdentity`], for
+/// routes used before the caller has a `User`, such as signing up.
+pub async fn require_token(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    mut request: Request<axum::body::Body>,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let auth_header = extract_auth_header(&headers).map_err(auth_error)?;
+    let identity = app_state
+        .auth_provider
+        .authenticate(&auth_header)
+        .await
+        .map_err(auth_error)?;
+
+    request.extensions_mut().insert(identity);
+    Ok(next.run(request).await)
+}
+
 pub type RequestUser = user::Data;
 
-pub fn permission_check(
-    user: RequestUser,
-    unrestricted_role: Role,
-    additional_check: Vec<Predicate>,
-) -> bool {
-    {
-        if user.privilege >= unrestricted_role {
-            return true;
-        }
-        for (role, check) in additional_check {
-            if user.privilege == role && check(user.clone()) {
-                return true;
-            }
-        }
-        false
+/// Reads the bearer token of a socket.io handshake from the `token` of its auth payload, falling
+/// back to the `Authorization` header for clients that can't send one.
+pub fn socket_token(headers: &HeaderMap, auth: &serde_json::Value) -> Result<String, ApiError> {
+    match auth.get("token").and_then(serde_json::Value::as_str) {
+        Some(token) => Ok(token.strip_prefix("Bearer ").unwrap_or(token).to_owned()),
+        None => extract_auth_header(headers).map_err(auth_error),
     }
 }
 
-pub async fn permission_check_socket(
-    headers: HeaderMap<HeaderValue>,
-    unrestricted_role: Role,
-) -> bool {
-    let auth_header = match extract_auth_header(&headers) {
-        Ok(header) => header,
-        Err(_) => return false,
-    };
-
-    let app_state = match APP_STATE.get() {
-        Some(state) => state,
-        None => return false,
-    };
-
-    let firebase_user = match fetch_firebase_user(&auth_header, app_state).await {
-        Ok(user) => user,
-        Err(_) => return false,
-    };
-
-    let user_uid = match firebase_user.users.first() {
-        Some(user) => user.local_id.clone(),
-        None => return false,
-    };
-
-    match app_state
-        .client
-        .user()
-        .find_unique(user::UniqueWhereParam::GcpIdEquals(user_uid))
-        .exec()
-        .await
-    {
-        Ok(Some(user)) => user.privilege >= unrestricted_role,
-        _ => false,
+/// Finds the user a socket.io handshake's token belongs to, like [`require_auth`] does for
+/// requests.
+pub async fn authenticate_socket(
+    token: &str,
+    app_state: &AppState,
+) -> Result<RequestUser, ApiError> {
+    find_token_user(token, app_state).await.map_err(auth_error)
+}
+
+/// Whether `user`'s role holds `permission` in the [`PermissionTable`].
+pub fn permission_check(user: &RequestUser, permission: Permission) -> bool {
+    PermissionTable::global().allows(user.privilege, permission)
+}
+
+/// Lets `user` through if they hold `permission`, otherwise only if `additional_check` resolves to
+/// true. The check is only awaited for callers without `permission`, so it can query the database,
+/// e.g. to see whether the caller owns the resource.
+///
+/// Fails with 403, as the caller is already authenticated.
+pub async fn permission_check_async(
+    user: &RequestUser,
+    permission: Permission,
+    additional_check: impl Future<Output = bool>,
+) -> Result<(), ApiError> {
+    if permission_check(user, permission) || additional_check.await {
+        Ok(())
+    } else {
+        Err(forbidden())
     }
 }
 
-pub fn permission_check_async<T>(
-    _user: RequestUser,
-    _organizer_roles: Vec<Role>,
-    _user_additional_check: fn(user::Data) -> T,
-) -> bool
-where
-    T: Future<Output = bool>,
-{
-    todo!()
+pub fn forbidden() -> ApiError {
+    ApiError::Forbidden("Forbidden".to_owned())
 }
 
-// Doesn't work that's why it's private. Will try to fix later as that will reduce code duplication
-fn _async_auth_router_layer() -> Router {
-    todo!()
+/// Extracts the authenticated user, rejecting with 403 unless their role holds `P`'s permission.
+/// Needs [`require_auth`] to have run, and rejects with 401 otherwise.
+///
+/// ```ignore
+/// async fn delete_hackathon(
+///     RequirePermission(user, _): RequirePermission<require::HackathonWrite>,
+/// ) { .. }
+/// ```
+pub struct RequirePermission<P: RequiredPermission>(pub RequestUser, pub PhantomData<P>);
+
+#[async_trait]
+impl<S, P> FromRequestParts<S> for RequirePermission<P>
+where
+    S: Send + Sync,
+    P: RequiredPermission,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let user = parts
+            .extensions
+            .get::<RequestUser>()
+            .cloned()
+            .ok_or(ApiError::Unauthorized)?;
+
+        if permission_check(&user, P::PERMISSION) {
+            Ok(Self(user, PhantomData))
+        } else {
+            Err(forbidden())
+        }
+    }
 }
@@ -0,0 +1,153 @@
+use std::{fmt::Debug, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use hyper::StatusCode;
+use jsonwebtoken::{
+    decode, encode, get_current_timestamp, Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::firebase_auth::FirebaseTokenVerifier;
+
+const LOCAL_ISSUER: &str = "api-rust-local";
+
+/// Who a verified bearer token belongs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthIdentity {
+    /// Matched against `User.gcpId`
+    pub uid: String,
+    /// Only set when the provider vouches for it, e.g. Firebase has verified it
+    pub email: Option<String>,
+}
+
+/// Turns the bearer token of a request into the identity of the user it belongs to.
+#[async_trait]
+pub trait AuthProvider: Debug + Send + Sync {
+    async fn authenticate(&self, token: &str) -> Result<AuthIdentity, StatusCode>;
+}
+
+#[async_trait]
+impl AuthProvider for FirebaseTokenVerifier {
+    async fn authenticate(&self, token: &str) -> Result<AuthIdentity, StatusCode> {
+        let claims = self.verify(token).await?;
+        let email = match claims.email_verified {
+            Some(true) => claims.email,
+            _ => None,
+        };
+        Ok(AuthIdentity {
+            uid: claims.sub,
+            email,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct LocalClaims {
+    sub: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    email: Option<String>,
+    iss: String,
+    iat: u64,
+    exp: u64,
+}
+
+/// Accepts HS256 tokens signed with a shared secret, so development and CI can authenticate
+/// without reaching Firebase. Tokens are minted with [`LocalJwtAuthProvider::issue_token`].
+pub struct LocalJwtAuthProvider {
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+}
+
+impl Debug for LocalJwtAuthProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LocalJwtAuthProvider")
+            .finish_non_exhaustive()
+    }
+}
+
+impl LocalJwtAuthProvider {
+    pub fn new(secret: &[u8]) -> Self {
+        Self {
+            encoding_key: EncodingKey::from_secret(secret),
+            decoding_key: DecodingKey::from_secret(secret),
+        }
+    }
+
+    /// Signs a token for the user whose `gcpId` is `gcp_id`, valid for `lifetime`.
+    pub fn issue_token(&self, gcp_id: &str, lifetime: Duration) -> String {
+        self.issue_identity_token(
+            &AuthIdentity {
+                uid: gcp_id.to_owned(),
+                email: None,
+            },
+            lifetime,
+        )
+    }
+
+    /// Like [`LocalJwtAuthProvider::issue_token`], but can also carry an email for
+    /// self-registration.
+    pub fn issue_identity_token(&self, identity: &AuthIdentity, lifetime: Duration) -> String {
+        let now = get_current_timestamp();
+        let claims = LocalClaims {
+            sub: identity.uid.clone(),
+            email: identity.email.clone(),
+            iss: LOCAL_ISSUER.to_owned(),
+            iat: now,
+            exp: now + lifetime.as_secs(),
+        };
+        encode(&Header::new(Algorithm::HS256), &claims, &self.encoding_key)
+            .expect("HS256 signing with an in-memory secret cannot fail")
+    }
+}
+
+#[async_trait]
+impl AuthProvider for LocalJwtAuthProvider {
+    async fn authenticate(&self, token: &str) -> Result<AuthIdentity, StatusCode> {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_issuer(&[LOCAL_ISSUER]);
+        validation.set_required_spec_claims(&["exp", "iss", "sub"]);
+
+        let claims = decode::<LocalClaims>(token, &self.decoding_key, &validation)
+            .map_err(|_| StatusCode::UNAUTHORIZED)?
+            .claims;
+        if claims.sub.is_empty() {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+        Ok(AuthIdentity {
+            uid: claims.sub,
+            email: claims.email,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct ExpiryClaim {
+    exp: u64,
+}
+
+/// Reads when a token expires, as seconds since the epoch, from its `exp` claim.
+///
+/// The signature isn't checked, so this is only for tokens an [`AuthProvider`] has already
+/// accepted. Both Firebase and local tokens carry `exp`.
+pub fn token_expiry(token: &str) -> Option<u64> {
+    let mut validation = Validation::default();
+    validation.insecure_disable_signature_validation();
+    validation.validate_exp = false;
+    validation.validate_aud = false;
+    decode::<ExpiryClaim>(token, &DecodingKey::from_secret(&[]), &validation)
+        .ok()
+        .map(|data| data.claims.exp)
+}
+
+/// Picks the provider from `AUTH_PROVIDER` (`firebase` or `local`), defaulting to `firebase`.
+/// The local provider signs with `LOCAL_AUTH_SECRET`.
+pub fn auth_provider_from_env(http: reqwest::Client) -> Arc<dyn AuthProvider> {
+    match std::env::var("AUTH_PROVIDER").as_deref() {
+        Ok("local") => {
+            let secret = std::env::var("LOCAL_AUTH_SECRET").expect("LOCAL_AUTH_SECRET must be set");
+            tracing::warn!("Authenticating with locally signed tokens, do not use in production");
+            Arc::new(LocalJwtAuthProvider::new(secret.as_bytes()))
+        }
+        _ => Arc::new(FirebaseTokenVerifier::from_env(http)),
+    }
+}
@@ -4,29 +4,14 @@ use axum::response::{IntoResponse, Response};
 use hyper::StatusCode;
 use sendgrid::SGClient;
 use socketioxide::SocketIo;
-use utoipa::ToSchema;
 
-use crate::{prisma::PrismaClient, storage_bucket_service::StorageBucketService};
-
-#[derive(ToSchema)]
-pub struct BaseError {
-    status_code: StatusCode,
-    message: String,
-}
-
-impl IntoResponse for BaseError {
-    fn into_response(self) -> Response {
-        (self.status_code, self.message).into_response()
-    }
-}
-impl BaseError {
-    pub fn new(status_code: StatusCode, message: String) -> Self {
-        BaseError {
-            status_code,
-            message,
-        }
-    }
-}
+use crate::{
+    api_error::ApiError,
+    auth_provider::{auth_provider_from_env, AuthProvider},
+    prisma::PrismaClient,
+    storage_bucket_service::StorageBucketService,
+    user_cache::RequestUserCache,
+};
 
 pub struct BaseResponse<T> {
     status_code: StatusCode,
@@ -64,11 +49,11 @@ impl BaseResponse<()> {
     }
 }
 
-type _StandardResponse<T> = Result<(StatusCode, T), (StatusCode, String)>;
-pub type GetResponse<T> = Result<(StatusCode, T), (StatusCode, String)>;
-pub type CreateResponse = Result<(StatusCode, ()), (StatusCode, String)>;
-pub type DeleteResponse = Result<(StatusCode, ()), (StatusCode, String)>;
-pub type UpdateResponse = Result<(StatusCode, ()), (StatusCode, String)>;
+type _StandardResponse<T> = Result<(StatusCode, T), ApiError>;
+pub type GetResponse<T> = Result<(StatusCode, T), ApiError>;
+pub type CreateResponse = Result<(StatusCode, ()), ApiError>;
+pub type DeleteResponse = Result<(StatusCode, ()), ApiError>;
+pub type UpdateResponse = Result<(StatusCode, ()), ApiError>;
 
 /* pub type UpdateResponse<T> = Result<BaseResponse<T>, BaseError>; */
 
@@ -79,6 +64,8 @@ pub struct AppState {
     pub io: Arc<SocketIo>,
     pub send_grid: sendgrid::SGClient,
     pub storage_bucket_service: Arc<StorageBucketService>,
+    pub auth_provider: Arc<dyn AuthProvider>,
+    pub user_cache: Arc<RequestUserCache>,
 }
 
 impl AppState {
@@ -89,13 +76,16 @@ impl AppState {
             .expect("Didn't connect to database");
 
         let sendgrid_key = std::env::var("SENDGRID_API_KEY").expect("SENDGRID_API_KEY must be set");
+        let reqwest_client = reqwest::Client::new();
 
         Self {
             client: Arc::new(client),
-            reqwest_client: reqwest::Client::new(),
+            auth_provider: auth_provider_from_env(reqwest_client.clone()),
+            reqwest_client,
             io: Arc::new(socket),
             send_grid: SGClient::new(sendgrid_key),
             storage_bucket_service: Arc::new(StorageBucketService::new()),
+            user_cache: Arc::new(RequestUserCache::from_env()),
         }
     }
 }
@@ -1 +1,23 @@
+use prisma_client_rust::{PrismaValue, QueryError, Raw};
 
+use crate::prisma::PrismaClient;
+
+/// Locks the row of `table` with `id` until the surrounding transaction ends, so checks made
+/// against it (a team's size, an event's occupancy) and the writes they allow happen one at a time,
+/// across every instance of the API.
+///
+/// `table` is interpolated into the query, so it must be a model name and never user input.
+pub async fn lock_for_update(
+    client: &PrismaClient,
+    table: &'static str,
+    id: String,
+) -> Result<(), QueryError> {
+    client
+        ._query_raw::<serde_json::Value>(Raw::new(
+            &format!("SELECT `id` FROM `{table}` WHERE `id` = {{}} FOR UPDATE"),
+            vec![PrismaValue::String(id)],
+        ))
+        .exec()
+        .await
+        .map(|_| ())
+}
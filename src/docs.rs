@@ -3,10 +3,13 @@ use utoipa::openapi::security::{
 };
 use utoipa::{Modify, OpenApi};
 
-use crate::entities;
-use crate::routes::{events, extra_credit_classes, hackathons, locations};
+use crate::routes::{
+    audit_logs, events, extra_credit_classes, hackathons, locations, metrics, organizers, projects,
+    registrations, scans, scores, sponsors, teams, users,
+};
+use crate::{api_error, entities, judge_assignment, occupancy, pagination, user_cache};
 
-use crate::{prisma, utils};
+use crate::{prisma, scoring, socket, utils};
 
 #[derive(OpenApi)]
 #[openapi(
@@ -15,6 +18,7 @@ use crate::{prisma, utils};
         hackathons::create_hackathon,
         hackathons::get_all_hackathon,
         hackathons::get_hackathon_by_id,
+        hackathons::update_hackathon_by_id,
         hackathons::delete_hackathon_by_id,
         hackathons::set_active_hackathon,
         hackathons::get_active_hackathon,
@@ -22,33 +26,167 @@ use crate::{prisma, utils};
         locations::create_location,
         locations::get_all_locations,
         locations::get_location_by_id,
+        locations::update_location_by_id,
         locations::delete_location_by_id,
+        locations::get_location_occupancy,
 
         events::create_event,
         events::get_all_events,
+        events::get_hackathon_events,
         events::get_event_by_id,
+        events::update_event_by_id,
         events::delete_event_by_id,
         events::check_in_user_to_event,
+        events::get_event_occupancy,
 
         extra_credit_classes::create_extra_credit_class,
         extra_credit_classes::get_all_extra_credit_classes,
+        extra_credit_classes::get_hackathon_extra_credit_classes,
         extra_credit_classes::get_extra_credit_class_by_id,
+        extra_credit_classes::update_extra_credit_class_by_id,
+
         extra_credit_classes::delete_extra_credit_class_by_id,
+
+        sponsors::create_sponsor,
+        sponsors::get_all_sponsors,
+        sponsors::get_hackathon_sponsors,
+        sponsors::update_sponsor_by_id,
+
+        scans::get_all_scans,
+        scans::get_hackathon_scans,
+
+        registrations::create_registration,
+        registrations::get_all_registrations,
+        registrations::get_my_registration,
+        registrations::get_registration_by_id,
+        registrations::update_registration_by_id,
+        registrations::withdraw_registration_by_id,
+        registrations::upload_my_resume,
+        registrations::download_resume_by_registration_id,
+
+        projects::create_project,
+        projects::get_all_projects,
+        projects::get_project_by_id,
+        projects::update_project_by_id,
+        projects::delete_project_by_id,
+        projects::assign_judge_to_project,
+        projects::unassign_judge_from_project,
+        projects::auto_assign_judges,
+
+        scores::get_my_scores,
+        scores::save_score,
+        scores::submit_score,
+        scores::get_leaderboard,
+
+        audit_logs::get_audit_logs,
+
+        metrics::get_auth_cache_stats,
+
+        organizers::get_all_organizers,
+        organizers::grant_privilege,
+        organizers::revoke_privilege,
+        organizers::get_privilege_changes,
+
+        teams::create_team,
+        teams::get_my_team,
+        teams::join_team,
+        teams::leave_team,
+        teams::regenerate_join_code,
+        teams::get_teamless_registrations,
+
+        users::get_all_users,
+        users::register_me,
+        users::get_me,
+        users::update_me,
     ),
     components(
         schemas(
+            api_error::ApiErrorBody,
+            api_error::FieldError,
+
+            pagination::SortOrder,
+            pagination::HackathonPage,
+            pagination::EventPage,
+            pagination::LocationPage,
+            pagination::RegistrationPage,
+            pagination::ProjectPage,
+            pagination::AuditLogPage,
+            pagination::ScanPage,
+            pagination::UserPage,
+            pagination::SponsorPage,
+
             entities::hackathons::CreateHackathonEntity,
             entities::hackathons::HackathonEntity,
+            entities::hackathons::HackathonUpdateEntity,
 
             locations::CreateLocationEntity,
             locations::LocationEntity,
+            locations::LocationUpdateEntity,
 
             events::CreateEventEntity,
             events::CheckInUserToEventEntity,
             prisma::EventType,
             events::EventEntity,
+            events::EventUpdateEntity,
+            events::ScanResultEntity,
+            events::ScannedHackerEntity,
+            prisma::ScanType,
+            scans::ScanEntity,
+            occupancy::EventOccupancy,
+            occupancy::LocationOccupancy,
 
             extra_credit_classes::CreateExtraCreditClassEntity,
+            extra_credit_classes::ExtraCreditClassUpdateEntity,
+
+            sponsors::CreateSponsorEntity,
+            sponsors::SponsorEntity,
+            sponsors::SponsorUpdateEntity,
+
+            registrations::RegistrationEntity,
+            registrations::CreateRegistrationEntity,
+            registrations::UpdateRegistrationEntity,
+            registrations::UploadResumeEntity,
+
+            projects::ProjectEntity,
+            projects::CreateProjectEntity,
+            projects::UpdateProjectEntity,
+            projects::AssignJudgeEntity,
+            projects::AutoAssignJudgesEntity,
+            judge_assignment::AssignmentPlan,
+            judge_assignment::JudgeAssignment,
+            judge_assignment::UnfilledProject,
+
+            scores::ScoreEntity,
+            scores::SaveScoreEntity,
+            scoring::AggregationMode,
+            scoring::Leaderboard,
+            scoring::LeaderboardEntry,
+            scoring::ChallengeRanking,
+            scoring::ChallengeEntry,
+
+            audit_logs::AuditLogEntity,
+
+            user_cache::CacheStats,
+
+            organizers::OrganizerEntity,
+            organizers::GrantPrivilegeEntity,
+            organizers::PrivilegeChangeEntity,
+
+            teams::TeamEntity,
+            teams::TeamMemberEntity,
+            teams::CreateTeamEntity,
+            teams::JoinTeamEntity,
+
+            users::UserEntity,
+            users::RegisterUserEntity,
+            users::UpdateProfileEntity,
+            users::ProfileEntity,
+
+            socket::ScanCreatedPayload,
+            socket::EventPayload,
+            socket::EventDeletedPayload,
+            socket::HackathonPayload,
+            socket::JoinAck,
         )
     ),
     modifiers(&SecurityAddon),
@@ -101,3 +239,50 @@ impl Modify for SecurityAddon {
 }
 
 pub struct SecurityAddon;
+
+/// Implements `ToSchema` for a partial update type. These are generated by prisma, which can't
+/// derive it, so their fields are listed in the invocation using the builders in [`partial`].
+macro_rules! partial_schema {
+    ($entity:ident { $($field:literal: $schema:expr),* $(,)? }) => {
+        impl<'__s> utoipa::ToSchema<'__s> for $entity {
+            fn schema() -> (
+                &'__s str,
+                utoipa::openapi::RefOr<utoipa::openapi::schema::Schema>,
+            ) {
+                (
+                    stringify!($entity),
+                    utoipa::openapi::ObjectBuilder::new()
+                        $(.property($field, $schema))*
+                        .into(),
+                )
+            }
+        }
+    };
+}
+
+pub(crate) use partial_schema;
+
+/// Property schemas for [`partial_schema!`]. Each is nullable, as any field can be left out.
+pub mod partial {
+    use utoipa::openapi::{KnownFormat, ObjectBuilder, SchemaFormat, SchemaType};
+
+    pub fn string() -> ObjectBuilder {
+        ObjectBuilder::new()
+            .schema_type(SchemaType::String)
+            .nullable(true)
+    }
+
+    pub fn integer() -> ObjectBuilder {
+        ObjectBuilder::new()
+            .schema_type(SchemaType::Integer)
+            .nullable(true)
+    }
+
+    pub fn date_time() -> ObjectBuilder {
+        string().format(Some(SchemaFormat::KnownFormat(KnownFormat::DateTime)))
+    }
+
+    pub fn one_of(values: &[&'static str]) -> ObjectBuilder {
+        string().enum_values(Some(values.iter().copied()))
+    }
+}
@@ -1,7 +1,12 @@
 use chrono::FixedOffset;
 use utoipa::ToSchema;
+use validator::{Validate, ValidationError, ValidationErrors};
 
-use crate::prisma::hackathon;
+use crate::{
+    docs::{partial, partial_schema},
+    prisma::hackathon,
+    validation::{self, PartialChecks},
+};
 
 #[derive(serde::Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -22,65 +27,40 @@ pub struct HackathonEntity {
     score: Option<Vec<prisma::score::Data>>, */
 }
 
-#[derive(serde::Deserialize, ToSchema)]
+#[derive(serde::Deserialize, ToSchema, Validate)]
 #[serde(rename_all = "camelCase")]
+#[validate(schema(function = "validate_hackathon_times"))]
 pub struct CreateHackathonEntity {
+    #[validate(length(min = 1))]
     pub name: String,
     pub start_time: chrono::DateTime<FixedOffset>,
     pub end_time: chrono::DateTime<FixedOffset>,
 }
 
+fn validate_hackathon_times(hackathon: &CreateHackathonEntity) -> Result<(), ValidationError> {
+    validation::validate_time_range(&hackathon.start_time, &hackathon.end_time)
+}
+
+// `active` is left out as only one hackathon can be active, which `/{id}/active` takes care of
 hackathon::partial_unchecked!(
     HackathonUpdateEntity {
         name
         start_time
         end_time
-        active
     }
 );
 
-// Recursive expansion of ToSchema macro
-// ======================================
-
-impl<'__s> utoipa::ToSchema<'__s> for HackathonUpdateEntity {
-    fn schema() -> (
-        &'__s str,
-        utoipa::openapi::RefOr<utoipa::openapi::schema::Schema>,
-    ) {
-        (
-            "HackathonUpdateEntity",
-            utoipa::openapi::ObjectBuilder::new()
-                .property(
-                    "name",
-                    utoipa::openapi::ObjectBuilder::new()
-                        .schema_type(utoipa::openapi::SchemaType::String)
-                        .nullable(true),
-                )
-                .property(
-                    "startTime",
-                    utoipa::openapi::ObjectBuilder::new()
-                        .schema_type(utoipa::openapi::SchemaType::String)
-                        .format(Some(utoipa::openapi::SchemaFormat::KnownFormat(
-                            utoipa::openapi::KnownFormat::DateTime,
-                        )))
-                        .nullable(true),
-                )
-                .property(
-                    "endTime",
-                    utoipa::openapi::ObjectBuilder::new()
-                        .schema_type(utoipa::openapi::SchemaType::String)
-                        .format(Some(utoipa::openapi::SchemaFormat::KnownFormat(
-                            utoipa::openapi::KnownFormat::DateTime,
-                        )))
-                        .nullable(true),
-                )
-                .property(
-                    "active",
-                    utoipa::openapi::ObjectBuilder::new()
-                        .schema_type(utoipa::openapi::SchemaType::Boolean)
-                        .nullable(true),
-                )
-                .into(),
-        )
+// The times are checked by the handler, against the stored ones if only one of them is changing
+impl Validate for HackathonUpdateEntity {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        PartialChecks::new()
+            .non_empty("name", self.name.as_ref())
+            .finish()
     }
 }
+
+partial_schema!(HackathonUpdateEntity {
+    "name": partial::string(),
+    "startTime": partial::date_time(),
+    "endTime": partial::date_time(),
+});
@@ -0,0 +1,236 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug},
+    time::{Duration, Instant},
+};
+
+use hyper::StatusCode;
+use jsonwebtoken::{decode, decode_header, jwk::JwkSet, Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use tokio::sync::{Mutex, RwLock};
+
+const FIREBASE_JWKS_ENDPOINT: &str =
+    "https://www.googleapis.com/service_accounts/v1/jwk/securetoken@system.gserviceaccount.com";
+const FIREBASE_ISSUER_PREFIX: &str = "https://securetoken.google.com/";
+/// Used when Google's response doesn't say how long its keys can be cached for
+const DEFAULT_KEY_LIFETIME: Duration = Duration::from_secs(60 * 60);
+/// Keys are refetched when they expire or a token is signed by a key we don't know, but no more
+/// often than this, whether or not the last attempt worked, so neither a stream of bad tokens nor
+/// an outage at Google has every request waiting on a fetch
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+/// How long a fetch of the keys may take, as requests needing a refresh wait for it
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The claims of a Firebase ID token that we rely on. `sub` is the Firebase uid, which is the
+/// `User.gcpId` it belongs to.
+#[derive(Deserialize, Debug, Clone)]
+pub struct FirebaseClaims {
+    pub sub: String,
+    pub email: Option<String>,
+    pub email_verified: Option<bool>,
+    pub auth_time: u64,
+    pub iat: u64,
+    pub exp: u64,
+}
+
+struct KeyCache {
+    keys: HashMap<String, DecodingKey>,
+    expires_at: Instant,
+    /// When a fetch was last started, successful or not
+    fetched_at: Option<Instant>,
+}
+
+impl KeyCache {
+    fn should_refresh(&self, key_id: &str, now: Instant) -> bool {
+        let stale = self.expires_at <= now || !self.keys.contains_key(key_id);
+        stale
+            && self
+                .fetched_at
+                .map_or(true, |at| now.duration_since(at) >= MIN_REFRESH_INTERVAL)
+    }
+}
+
+/// Verifies Firebase ID tokens locally against Google's published signing keys, following
+/// <https://firebase.google.com/docs/auth/admin/verify-id-tokens#verify_id_tokens_using_a_third-party_jwt_library>.
+///
+/// Keys are cached for as long as Google's `Cache-Control` header allows and refetched when they
+/// expire or a token names a key id we haven't seen, which is how rotations show up. If Google
+/// can't be reached the last known keys keep being used, past their expiry, until a refetch works.
+pub struct FirebaseTokenVerifier {
+    project_id: String,
+    http: reqwest::Client,
+    cache: RwLock<KeyCache>,
+    /// Held while fetching keys, which is done without locking the cache
+    refresh: Mutex<()>,
+    /// Off for verifiers built from fixed keys
+    remote: bool,
+}
+
+impl Debug for FirebaseTokenVerifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FirebaseTokenVerifier")
+            .field("project_id", &self.project_id)
+            .field("remote", &self.remote)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FirebaseTokenVerifier {
+    pub fn new(project_id: String, http: reqwest::Client) -> Self {
+        Self {
+            project_id,
+            http,
+            cache: RwLock::new(KeyCache {
+                keys: HashMap::new(),
+                expires_at: Instant::now(),
+                fetched_at: None,
+            }),
+            refresh: Mutex::new(()),
+            remote: true,
+        }
+    }
+
+    /// Reads the project id from `FIREBASE_PROJECT_ID`.
+    pub fn from_env(http: reqwest::Client) -> Self {
+        let project_id =
+            std::env::var("FIREBASE_PROJECT_ID").expect("FIREBASE_PROJECT_ID must be set");
+        Self::new(project_id, http)
+    }
+
+    /// A verifier that only trusts `keys` and never contacts Google.
+    pub fn with_keys(project_id: String, keys: HashMap<String, DecodingKey>) -> Self {
+        Self {
+            project_id,
+            http: reqwest::Client::builder()
+                .timeout(FETCH_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            cache: RwLock::new(KeyCache {
+                keys,
+                expires_at: Instant::now() + Duration::from_secs(u64::from(u32::MAX)),
+                fetched_at: None,
+            }),
+            refresh: Mutex::new(()),
+            remote: false,
+        }
+    }
+
+    /// Checks the token's signature, algorithm, issuer, audience, expiry, issue time and subject,
+    /// returning its claims if it is a valid ID token for this project.
+    pub async fn verify(&self, token: &str) -> Result<FirebaseClaims, StatusCode> {
+        let header = decode_header(token).map_err(|_| StatusCode::UNAUTHORIZED)?;
+        if header.alg != Algorithm::RS256 {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+        let key_id = header.kid.ok_or(StatusCode::UNAUTHORIZED)?;
+        let key = self.key(&key_id).await?;
+
+        let mut validation = Validation::new(Algorithm::RS256);
+        validation.set_issuer(&[format!("{FIREBASE_ISSUER_PREFIX}{}", self.project_id)]);
+        validation.set_audience(&[&self.project_id]);
+        validation.set_required_spec_claims(&["exp", "iat", "iss", "aud", "sub"]);
+
+        let claims = decode::<FirebaseClaims>(token, &key, &validation)
+            .map_err(|_| StatusCode::UNAUTHORIZED)?
+            .claims;
+
+        let latest = jsonwebtoken::get_current_timestamp() + validation.leeway;
+        if claims.sub.is_empty() || claims.iat > latest || claims.auth_time > latest {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+        Ok(claims)
+    }
+
+    async fn key(&self, key_id: &str) -> Result<DecodingKey, StatusCode> {
+        let cached = {
+            let cache = self.cache.read().await;
+            if !self.remote || !cache.should_refresh(key_id, Instant::now()) {
+                return cache
+                    .keys
+                    .get(key_id)
+                    .cloned()
+                    .ok_or(StatusCode::UNAUTHORIZED);
+            }
+            cache.keys.get(key_id).cloned()
+        };
+
+        // Only one request fetches at a time. The others keep using the key if it's cached, even
+        // expired, and otherwise wait to see whether the fetch brings it
+        let _refreshing = match self.refresh.try_lock() {
+            Ok(guard) => guard,
+            Err(_) => match cached {
+                Some(key) => return Ok(key),
+                None => self.refresh.lock().await,
+            },
+        };
+        {
+            let mut cache = self.cache.write().await;
+            // A fetch may have finished while we waited
+            if !cache.should_refresh(key_id, Instant::now()) {
+                return cache
+                    .keys
+                    .get(key_id)
+                    .cloned()
+                    .ok_or(StatusCode::UNAUTHORIZED);
+            }
+            cache.fetched_at = Some(Instant::now());
+        }
+
+        let fetched = self.fetch_keys().await;
+        let mut cache = self.cache.write().await;
+        match fetched {
+            Ok((keys, lifetime)) => {
+                cache.keys = keys;
+                cache.expires_at = Instant::now() + lifetime;
+            }
+            Err(err) => {
+                tracing::warn!("Failed to refresh Firebase signing keys, using cached keys: {err}")
+            }
+        }
+
+        cache
+            .keys
+            .get(key_id)
+            .cloned()
+            .ok_or(StatusCode::UNAUTHORIZED)
+    }
+
+    async fn fetch_keys(&self) -> Result<(HashMap<String, DecodingKey>, Duration), reqwest::Error> {
+        let response = self
+            .http
+            .get(FIREBASE_JWKS_ENDPOINT)
+            .timeout(FETCH_TIMEOUT)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let lifetime = response
+            .headers()
+            .get(reqwest::header::CACHE_CONTROL)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_max_age)
+            .unwrap_or(DEFAULT_KEY_LIFETIME);
+
+        let jwks: JwkSet = response.json().await?;
+        let keys = jwks
+            .keys
+            .iter()
+            .filter_map(|jwk| {
+                let key_id = jwk.common.key_id.clone()?;
+                let key = DecodingKey::from_jwk(jwk).ok()?;
+                Some((key_id, key))
+            })
+            .collect();
+
+        Ok((keys, lifetime))
+    }
+}
+
+/// Reads `max-age` out of a `Cache-Control` header value.
+pub fn parse_max_age(cache_control: &str) -> Option<Duration> {
+    cache_control
+        .split(',')
+        .filter_map(|directive| directive.trim().strip_prefix("max-age="))
+        .find_map(|seconds| seconds.trim().parse().ok())
+        .map(Duration::from_secs)
+}
@@ -0,0 +1,227 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// A project waiting for judges, along with the users on its team.
+#[derive(Clone, Debug)]
+pub struct ProjectToJudge {
+    pub project_id: String,
+    pub member_ids: HashSet<String>,
+}
+
+impl ProjectToJudge {
+    /// Judges can't score the project of a team they're on.
+    pub fn can_be_judged_by(&self, judge_id: &str) -> bool {
+        !self.member_ids.contains(judge_id)
+    }
+}
+
+#[derive(Serialize, ToSchema, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct JudgeAssignment {
+    pub project_id: String,
+    pub judge_id: String,
+}
+
+#[derive(Serialize, ToSchema, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UnfilledProject {
+    pub project_id: String,
+    pub missing_judges: usize,
+}
+
+#[derive(Serialize, ToSchema, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AssignmentPlan {
+    pub assignments: Vec<JudgeAssignment>,
+    pub unfilled_projects: Vec<UnfilledProject>,
+}
+
+/// Spreads judges across projects so every project ends up with `judges_per_project` distinct
+/// judges, no judge exceeds `max_projects_per_judge`, and nobody judges their own team.
+///
+/// `existing` holds assignments that are already in place; they count towards both the per
+/// project total and the judge's load, and are not repeated in the returned plan.
+///
+/// The plan is built with augmenting paths, so a project is only left short when no reshuffle of
+/// the new assignments could cover it. Judges' load ceiling is raised one project at a time,
+/// which keeps the work evenly spread instead of piling onto whoever sorts first.
+pub fn plan_assignments(
+    projects: &[ProjectToJudge],
+    judge_ids: &[String],
+    existing: &[JudgeAssignment],
+    judges_per_project: usize,
+    max_projects_per_judge: usize,
+) -> AssignmentPlan {
+    let mut planner = Planner::new(projects, judge_ids, existing, judges_per_project);
+
+    // Fill the most constrained projects first so they don't lose out on the few judges they can have
+    let mut order: Vec<usize> = (0..projects.len()).collect();
+    order.sort_by_key(|&project| {
+        let candidates = (0..judge_ids.len())
+            .filter(|&judge| planner.eligible[project][judge])
+            .count();
+        (candidates, &projects[project].project_id)
+    });
+
+    // No judge can take on more than every project on top of what they already have, so higher
+    // levels would only repeat the last one
+    let busiest = planner.load.iter().copied().max().unwrap_or(0);
+    let max_level = max_projects_per_judge.min(busiest + projects.len());
+    for level in 1..=max_level {
+        if planner.need.iter().all(|&need| need == 0) {
+            break;
+        }
+        loop {
+            let mut progressed = false;
+            // One judge per project per pass so projects fill up evenly
+            for &project in &order {
+                if planner.need[project] > 0 && planner.augment(project, level) {
+                    planner.need[project] -= 1;
+                    progressed = true;
+                }
+            }
+            if !progressed {
+                break;
+            }
+        }
+    }
+
+    let mut plan = AssignmentPlan::default();
+    for &project in &order {
+        for judge in 0..judge_ids.len() {
+            if planner.assigned[project][judge] {
+                plan.assignments.push(JudgeAssignment {
+                    project_id: projects[project].project_id.clone(),
+                    judge_id: judge_ids[judge].clone(),
+                });
+            }
+        }
+        if planner.need[project] > 0 {
+            plan.unfilled_projects.push(UnfilledProject {
+                project_id: projects[project].project_id.clone(),
+                missing_judges: planner.need[project],
+            });
+        }
+    }
+    plan
+}
+
+struct Planner {
+    /// Judge may be newly assigned to the project
+    eligible: Vec<Vec<bool>>,
+    /// Judge is newly assigned to the project by this plan
+    assigned: Vec<Vec<bool>>,
+    need: Vec<usize>,
+    load: Vec<usize>,
+}
+
+impl Planner {
+    fn new(
+        projects: &[ProjectToJudge],
+        judge_ids: &[String],
+        existing: &[JudgeAssignment],
+        judges_per_project: usize,
+    ) -> Self {
+        let project_index: HashMap<&str, usize> = projects
+            .iter()
+            .enumerate()
+            .map(|(index, project)| (project.project_id.as_str(), index))
+            .collect();
+        let judge_index: HashMap<&str, usize> = judge_ids
+            .iter()
+            .enumerate()
+            .map(|(index, judge_id)| (judge_id.as_str(), index))
+            .collect();
+
+        let mut eligible: Vec<Vec<bool>> = projects
+            .iter()
+            .map(|project| {
+                judge_ids
+                    .iter()
+                    .map(|judge_id| project.can_be_judged_by(judge_id))
+                    .collect()
+            })
+            .collect();
+        let mut already_judged = vec![0; projects.len()];
+        let mut load = vec![0; judge_ids.len()];
+
+        for assignment in existing {
+            let project = project_index.get(assignment.project_id.as_str());
+            let judge = judge_index.get(assignment.judge_id.as_str());
+            if let Some(&judge) = judge {
+                load[judge] += 1;
+            }
+            if let Some(&project) = project {
+                already_judged[project] += 1;
+                if let Some(&judge) = judge {
+                    eligible[project][judge] = false;
+                }
+            }
+        }
+
+        Self {
+            eligible,
+            assigned: vec![vec![false; judge_ids.len()]; projects.len()],
+            need: already_judged
+                .into_iter()
+                .map(|count| judges_per_project.saturating_sub(count))
+                .collect(),
+            load,
+        }
+    }
+
+    /// Finds one more judge for `start`, moving other new assignments around if every eligible
+    /// judge is already at `level`. Returns whether a judge was found.
+    fn augment(&mut self, start: usize, level: usize) -> bool {
+        let judge_count = self.load.len();
+        // For each reached project, the (project, judge) hop used to get there
+        let mut parent: Vec<Option<(usize, usize)>> = vec![None; self.need.len()];
+        let mut visited = vec![false; self.need.len()];
+        let mut queue = VecDeque::from([start]);
+        visited[start] = true;
+
+        while let Some(project) = queue.pop_front() {
+            let mut candidates: Vec<usize> = (0..judge_count)
+                .filter(|&judge| self.eligible[project][judge] && !self.assigned[project][judge])
+                .collect();
+            candidates.sort_by_key(|&judge| self.load[judge]);
+
+            if let Some(&judge) = candidates.iter().find(|&&judge| self.load[judge] < level) {
+                self.load[judge] += 1;
+                self.apply(project, judge, &parent);
+                return true;
+            }
+
+            // Every candidate is busy; see if one of their other new projects can switch judges
+            for &judge in &candidates {
+                for other in 0..self.need.len() {
+                    if !visited[other] && self.assigned[other][judge] {
+                        visited[other] = true;
+                        parent[other] = Some((project, judge));
+                        queue.push_back(other);
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// Assigns `judge` to `project` and walks the path back to the start, handing each freed
+    /// judge to the project that asked for it.
+    fn apply(&mut self, project: usize, judge: usize, parent: &[Option<(usize, usize)>]) {
+        let (mut project, mut judge) = (project, judge);
+        loop {
+            self.assigned[project][judge] = true;
+            match parent[project] {
+                Some((previous, freed_judge)) => {
+                    self.assigned[project][freed_judge] = false;
+                    project = previous;
+                    judge = freed_judge;
+                }
+                None => break,
+            }
+        }
+    }
+}
@@ -32,19 +32,30 @@
     clippy::absolute_paths
 )]
 
+pub mod api_error;
 pub mod app;
+pub mod audit;
 pub mod auth_guard;
+pub mod auth_provider;
 pub mod base_types;
 pub mod database;
 pub mod docs;
 pub mod email_service;
 pub mod entities;
+pub mod firebase_auth;
+pub mod judge_assignment;
+pub mod occupancy;
+pub mod pagination;
+pub mod permissions;
 #[allow(warnings)]
 pub mod prisma;
 pub mod routes;
+pub mod scoring;
 pub mod socket;
 pub mod storage_bucket_service;
+pub mod user_cache;
 pub mod utils;
+pub mod validation;
 
 use std::net::SocketAddr;
 
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use prisma_client_rust::{Direction, QueryError};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::{
+    api_error::ApiError,
+    prisma::{event, location, scan, PrismaClient, ScanType},
+};
+
+/// How many people are at an event right now.
+#[derive(Serialize, ToSchema, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EventOccupancy {
+    pub event_id: String,
+    pub location_id: String,
+    pub hackathon_id: String,
+    /// The event's capacity, or its location's if the event has none. Left out when unlimited
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<i32>,
+    /// Hackers whose latest scan at the event is a check in
+    pub occupancy: i64,
+}
+
+impl EventOccupancy {
+    pub fn is_full(&self) -> bool {
+        matches!(self.capacity, Some(capacity) if self.occupancy >= i64::from(capacity))
+    }
+}
+
+/// How many people are at a location, counting the events happening there now.
+#[derive(Serialize, ToSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LocationOccupancy {
+    pub location_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<i32>,
+    pub occupancy: i64,
+    pub events: Vec<EventOccupancy>,
+}
+
+/// Counts the hackers whose latest scan is a check in, given an event's scans oldest first as
+/// registration id and type.
+pub fn count_occupancy<'a>(scans: impl IntoIterator<Item = (&'a str, ScanType)>) -> i64 {
+    let latest: HashMap<_, _> = scans.into_iter().collect();
+    let present = latest
+        .values()
+        .filter(|scan_type| **scan_type == ScanType::CheckIn)
+        .count();
+    i64::try_from(present).unwrap_or(i64::MAX)
+}
+
+// Scans from before times were recorded sort first, as MySQL puts NULL first, and a check out made
+// in the same instant as a check in is taken to come after it
+fn scan_order(direction: Direction) -> [scan::OrderByParam; 2] {
+    [
+        scan::created_at::order(direction.clone()),
+        scan::r#type::order(direction),
+    ]
+}
+
+/// The scan that decides whether the hacker is at the event now.
+pub async fn latest_scan(
+    client: &PrismaClient,
+    event_id: String,
+    registration_id: String,
+) -> Result<Option<scan::Data>, QueryError> {
+    let [by_time, by_type] = scan_order(Direction::Desc);
+    client
+        .scan()
+        .find_first(vec![
+            scan::event_id::equals(event_id),
+            scan::registration_id::equals(registration_id),
+        ])
+        .order_by(by_time)
+        .order_by(by_type)
+        .exec()
+        .await
+}
+
+async fn count_event(
+    client: &PrismaClient,
+    event: &event::Data,
+    location_capacity: Option<i32>,
+) -> Result<EventOccupancy, ApiError> {
+    let [by_time, by_type] = scan_order(Direction::Asc);
+    let scans = client
+        .scan()
+        .find_many(vec![scan::event_id::equals(event.id.clone())])
+        .order_by(by_time)
+        .order_by(by_type)
+        .exec()
+        .await?;
+    let occupancy = count_occupancy(
+        scans
+            .iter()
+            .map(|scan| (scan.registration_id.as_str(), scan.r#type)),
+    );
+
+    Ok(EventOccupancy {
+        event_id: event.id.clone(),
+        location_id: event.location_id.clone(),
+        hackathon_id: event.hackathon_id.clone(),
+        capacity: event.capacity.or(location_capacity),
+        occupancy,
+    })
+}
+
+pub async fn event_occupancy(
+    client: &PrismaClient,
+    event_id: String,
+) -> Result<EventOccupancy, ApiError> {
+    let event = client
+        .event()
+        .find_unique(event::UniqueWhereParam::IdEquals(event_id))
+        .with(event::location::fetch())
+        .exec()
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Event not found".to_owned()))?;
+    let location_capacity = event.location().ok().and_then(|location| location.capacity);
+
+    count_event(client, &event, location_capacity).await
+}
+
+pub async fn location_occupancy(
+    client: &PrismaClient,
+    location_id: String,
+) -> Result<LocationOccupancy, ApiError> {
+    let now = chrono::Utc::now().fixed_offset();
+    let location = client
+        .location()
+        .find_unique(location::UniqueWhereParam::IdEquals(location_id))
+        .with(location::event::fetch(vec![
+            event::start_time::lte(now),
+            event::end_time::gt(now),
+        ]))
+        .exec()
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Location not found".to_owned()))?;
+
+    let mut events = vec![];
+    for event in location.event().map_or(&[][..], Vec::as_slice) {
+        events.push(count_event(client, event, location.capacity).await?);
+    }
+    Ok(LocationOccupancy {
+        occupancy: events.iter().map(|event| event.occupancy).sum(),
+        location_id: location.id,
+        capacity: location.capacity,
+        events,
+    })
+}
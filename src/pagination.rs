@@ -0,0 +1,130 @@
+use prisma_client_rust::Direction;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use validator::{Validate, ValidationError};
+
+use crate::{
+    entities::hackathons::HackathonEntity,
+    routes::{
+        audit_logs::AuditLogEntity, events::EventEntity, locations::LocationEntity,
+        projects::ProjectEntity, registrations::RegistrationEntity, scans::ScanEntity,
+        sponsors::SponsorEntity, users::UserEntity,
+    },
+    validation::field_error,
+};
+
+pub const DEFAULT_LIMIT: i64 = 25;
+pub const MAX_LIMIT: i64 = 100;
+
+#[derive(Deserialize, ToSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+/// Pagination shared by the list endpoints, read from the query string next to each endpoint's own
+/// filters and `sort` field.
+///
+/// Pages are either taken by `offset`, or by `cursor` to continue after the last item of the
+/// previous page, which doesn't skip or repeat items when rows are added in between.
+#[derive(Deserialize, IntoParams, Validate, Default)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+#[validate(schema(function = "validate_page_params"))]
+pub struct PageParams {
+    /// Items per page, from 1 to 100. Defaults to 25
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<i64>,
+    /// Items to skip. Can't be combined with `cursor`
+    #[validate(range(min = 0))]
+    pub offset: Option<i64>,
+    /// The `nextCursor` of the previous page
+    pub cursor: Option<String>,
+    /// asc or desc. Defaults to the endpoint's usual order
+    #[param(inline)]
+    pub order: Option<SortOrder>,
+}
+
+fn validate_page_params(params: &PageParams) -> Result<(), ValidationError> {
+    if params.cursor.is_some() && params.offset.is_some() {
+        Err(field_error(
+            "offset",
+            "cursor_and_offset",
+            "offset can't be combined with cursor",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+impl PageParams {
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+
+    /// One more than [`Self::limit`], so [`Self::page`] can tell whether there's a next page.
+    pub fn take(&self) -> i64 {
+        self.limit() + 1
+    }
+
+    /// Prisma includes the cursor's row in the results, so it's skipped to start after it.
+    pub fn skip(&self) -> i64 {
+        if self.cursor.is_some() {
+            1
+        } else {
+            self.offset.unwrap_or(0).max(0)
+        }
+    }
+
+    pub fn direction(&self, default: SortOrder) -> Direction {
+        match self.order.unwrap_or(default) {
+            SortOrder::Asc => Direction::Asc,
+            SortOrder::Desc => Direction::Desc,
+        }
+    }
+
+    /// Wraps the rows fetched with [`Self::take`], dropping the extra one and making the cursor to
+    /// continue from with `cursor_of` if it was there.
+    pub fn page<T>(
+        &self,
+        mut items: Vec<T>,
+        total: i64,
+        cursor_of: impl Fn(&T) -> String,
+    ) -> Page<T> {
+        let limit = usize::try_from(self.limit()).unwrap_or(usize::MAX);
+        let next_cursor = if items.len() > limit {
+            items.truncate(limit);
+            items.last().map(cursor_of)
+        } else {
+            None
+        };
+        Page {
+            items,
+            total,
+            next_cursor,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+#[aliases(
+    HackathonPage = Page<HackathonEntity>,
+    EventPage = Page<EventEntity>,
+    LocationPage = Page<LocationEntity>,
+    RegistrationPage = Page<RegistrationEntity>,
+    ProjectPage = Page<ProjectEntity>,
+    AuditLogPage = Page<AuditLogEntity>,
+    ScanPage = Page<ScanEntity>,
+    UserPage = Page<UserEntity>,
+    SponsorPage = Page<SponsorEntity>
+)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Items matching the filters across every page
+    pub total: i64,
+    /// Pass as `cursor` for the next page. Left out on the last page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
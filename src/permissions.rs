@@ -0,0 +1,202 @@
+use std::{collections::HashSet, sync::OnceLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::prisma::Role;
+
+/// Implemented by the marker types in [`require`], for use with
+/// [`crate::auth_guard::RequirePermission`].
+pub trait RequiredPermission: Send + Sync {
+    const PERMISSION: Permission;
+}
+
+macro_rules! permissions {
+    ($($permission:ident => $name:literal),* $(,)?) => {
+        /// Something a role may be allowed to do, named `resource:action`.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+        pub enum Permission {
+            $(
+                #[serde(rename = $name)]
+                $permission,
+            )*
+        }
+
+        impl Permission {
+            pub const ALL: &'static [Self] = &[$(Self::$permission),*];
+
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $(Self::$permission => $name,)*
+                }
+            }
+        }
+
+        /// One marker type per [`Permission`], so a handler can name the permission it needs
+        /// in its extractor, e.g. `RequirePermission<require::HackathonWrite>`.
+        pub mod require {
+            $(
+                pub struct $permission;
+
+                impl super::RequiredPermission for $permission {
+                    const PERMISSION: super::Permission = super::Permission::$permission;
+                }
+            )*
+        }
+    };
+}
+
+permissions! {
+    HackathonRead => "hackathon:read",
+    HackathonWrite => "hackathon:write",
+    EventWrite => "event:write",
+    ScansRead => "scans:read",
+    LocationRead => "location:read",
+    LocationWrite => "location:write",
+    UserRead => "user:read",
+    UserDelete => "user:delete",
+    RegistrationRead => "registration:read",
+    RegistrationWrite => "registration:write",
+    ResumesRead => "resumes:read",
+    ProjectRead => "project:read",
+    ProjectWrite => "project:write",
+    ProjectDelete => "project:delete",
+    JudgesAssign => "judges:assign",
+    JudgesAutoAssign => "judges:auto_assign",
+    ScoresWrite => "scores:write",
+    ScoresRead => "scores:read",
+    OrganizersManage => "organizers:manage",
+    AuditRead => "audit:read",
+    MetricsRead => "metrics:read",
+    ReimbursementsApprove => "reimbursements:approve",
+    SocketAdmin => "socket:admin",
+    SocketExec => "socket:exec",
+}
+
+/// The permissions granted to one role, as written in the file named by `PERMISSIONS_FILE`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct RoleGrant {
+    pub role: Role,
+    pub permissions: HashSet<Permission>,
+}
+
+/// Which permissions each role holds. A role missing from the table holds none.
+#[derive(Debug, Clone)]
+pub struct PermissionTable {
+    grants: Vec<RoleGrant>,
+}
+
+static PERMISSION_TABLE: OnceLock<PermissionTable> = OnceLock::new();
+
+impl PermissionTable {
+    pub fn new(grants: Vec<RoleGrant>) -> Self {
+        Self { grants }
+    }
+
+    /// Reads the table from the JSON file named by `PERMISSIONS_FILE`, a list of
+    /// `{ "role": "EXEC", "permissions": ["hackathon:write", ...] }` objects, falling back to
+    /// [`PermissionTable::default`] when the variable is unset.
+    pub fn from_env() -> Self {
+        match std::env::var("PERMISSIONS_FILE") {
+            Ok(path) => {
+                let file = std::fs::read_to_string(&path)
+                    .unwrap_or_else(|err| panic!("Couldn't read PERMISSIONS_FILE {path}: {err}"));
+                let grants = serde_json::from_str(&file)
+                    .unwrap_or_else(|err| panic!("Invalid PERMISSIONS_FILE {path}: {err}"));
+                tracing::info!("Loaded role permissions from {path}");
+                Self::new(grants)
+            }
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// The table the API checks against, loaded with [`PermissionTable::from_env`] on first use.
+    pub fn global() -> &'static Self {
+        PERMISSION_TABLE.get_or_init(Self::from_env)
+    }
+
+    pub fn permissions(&self, role: Role) -> HashSet<Permission> {
+        self.grants
+            .iter()
+            .filter(|grant| grant.role == role)
+            .flat_map(|grant| grant.permissions.iter().copied())
+            .collect()
+    }
+
+    pub fn allows(&self, role: Role, permission: Permission) -> bool {
+        self.grants
+            .iter()
+            .any(|grant| grant.role == role && grant.permissions.contains(&permission))
+    }
+
+    /// Every role holding `permission`, for building `privilege::in_vec` filters.
+    pub fn roles_with(&self, permission: Permission) -> Vec<Role> {
+        Role::ALL
+            .into_iter()
+            .filter(|role| self.allows(*role, permission))
+            .collect()
+    }
+
+    /// Whether `role` holds every permission `other` does, i.e. `other` is not more powerful.
+    pub fn covers(&self, role: Role, other: Role) -> bool {
+        self.permissions(other).is_subset(&self.permissions(role))
+    }
+}
+
+impl Default for PermissionTable {
+    /// Sponsors read resumes; Volunteers judge; Team run the event day to day; Exec and Tech
+    /// additionally manage hackathons, scores and the audit trail; Finance get Team's permissions
+    /// plus reimbursements.
+    fn default() -> Self {
+        use Permission::*;
+
+        let sponsor = vec![ResumesRead];
+        let volunteer = vec![ProjectRead, ScoresWrite];
+        let team = [
+            volunteer.clone(),
+            vec![
+                HackathonRead,
+                EventWrite,
+                ScansRead,
+                LocationRead,
+                LocationWrite,
+                UserRead,
+                UserDelete,
+                RegistrationRead,
+                RegistrationWrite,
+                ResumesRead,
+                ProjectWrite,
+                JudgesAssign,
+                OrganizersManage,
+                SocketAdmin,
+            ],
+        ]
+        .concat();
+        let exec = [
+            team.clone(),
+            vec![
+                HackathonWrite,
+                ProjectDelete,
+                JudgesAutoAssign,
+                ScoresRead,
+                AuditRead,
+                MetricsRead,
+                SocketExec,
+            ],
+        ]
+        .concat();
+        let finance = [team.clone(), vec![ReimbursementsApprove]].concat();
+
+        let grant = |role, permissions: Vec<Permission>| RoleGrant {
+            role,
+            permissions: permissions.into_iter().collect(),
+        };
+        Self::new(vec![
+            grant(Role::Volunteer, volunteer),
+            grant(Role::Team, team),
+            grant(Role::Exec, exec.clone()),
+            grant(Role::Tech, exec),
+            grant(Role::Finance, finance),
+            grant(Role::Sponsor, sponsor),
+        ])
+    }
+}
@@ -0,0 +1,123 @@
+use axum::{extract::State, middleware, routing::get, Json, Router};
+use chrono::FixedOffset;
+use hyper::StatusCode;
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+use crate::{
+    api_error::ApiError,
+    auth_guard::{self, RequirePermission},
+    base_types::{AppState, GetResponse},
+    pagination::{Page, PageParams, SortOrder},
+    permissions::require,
+    prisma::{audit_log, AuditAction, Role},
+    validation::ValidQuery,
+};
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[allow(unused)]
+pub struct AuditLogEntity {
+    id: String,
+    actor_id: String,
+    #[schema(value_type = String)]
+    actor_role: Role,
+    /// CREATE, UPDATE or DELETE
+    #[schema(value_type = String)]
+    action: AuditAction,
+    entity_type: String,
+    entity_id: String,
+    before: Option<serde_json::Value>,
+    after: Option<serde_json::Value>,
+    time: chrono::DateTime<FixedOffset>,
+}
+
+#[derive(Deserialize, IntoParams, Validate)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct AuditLogParams {
+    actor_id: Option<String>,
+    /// e.g. hackathon, user, event, location
+    entity_type: Option<String>,
+    entity_id: Option<String>,
+    /// Only entries at or after this time
+    from: Option<chrono::DateTime<FixedOffset>>,
+    /// Only entries before this time
+    to: Option<chrono::DateTime<FixedOffset>>,
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    get,
+    context_path = "/audit-logs",
+    path = "",
+    responses(
+        (status = 200, description = "Returns a page of matching audit entries, newest first", body = AuditLogPage),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 422, description = "Invalid query parameters")
+    ),
+    params(PageParams, AuditLogParams),
+    security(
+        ("api_key" = ["audit:read"])
+    )
+)]
+pub async fn get_audit_logs(
+    State(app_state): State<AppState>,
+    ValidQuery(page): ValidQuery<PageParams>,
+    ValidQuery(params): ValidQuery<AuditLogParams>,
+    _: RequirePermission<require::AuditRead>,
+) -> GetResponse<Json<Page<audit_log::Data>>> {
+    let mut filters = vec![];
+    if let Some(actor_id) = params.actor_id {
+        filters.push(audit_log::actor_id::equals(actor_id));
+    }
+    if let Some(entity_type) = params.entity_type {
+        filters.push(audit_log::entity_type::equals(entity_type));
+    }
+    if let Some(entity_id) = params.entity_id {
+        filters.push(audit_log::entity_id::equals(entity_id));
+    }
+    if let Some(from) = params.from {
+        filters.push(audit_log::time::gte(from));
+    }
+    if let Some(to) = params.to {
+        filters.push(audit_log::time::lt(to));
+    }
+
+    let direction = || page.direction(SortOrder::Desc);
+    let mut query = app_state
+        .client
+        .audit_log()
+        .find_many(filters.clone())
+        .order_by(audit_log::time::order(direction()))
+        .order_by(audit_log::id::order(direction()))
+        .skip(page.skip())
+        .take(page.take());
+    if let Some(cursor) = page.cursor.clone() {
+        query = query.cursor(audit_log::UniqueWhereParam::IdEquals(cursor));
+    }
+
+    match app_state
+        .client
+        ._batch((query, app_state.client.audit_log().count(filters)))
+        .await
+    {
+        Ok((entries, total)) => Ok((
+            StatusCode::OK,
+            Json(page.page(entries, total, |entry| entry.id.clone())),
+        )),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub fn audit_log_get_router(app_state: AppState) -> Router {
+    Router::new()
+        .route("/", get(get_audit_logs))
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            auth_guard::require_auth,
+        ))
+        .with_state(app_state)
+}
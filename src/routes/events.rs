@@ -2,19 +2,32 @@ use axum::{
     debug_handler,
     extract::{Path, State},
     middleware,
-    routing::{delete, get, post},
-    Extension, Json, Router,
+    routing::{get, patch, post},
+    Json, Router,
 };
 use chrono::FixedOffset;
 use hyper::StatusCode;
 
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
+use validator::{Validate, ValidationError, ValidationErrors};
 
 use crate::{
-    auth_guard::{self, permission_check, RequestUser},
-    base_types::{AppState, CreateResponse, DeleteResponse, GetResponse},
-    prisma::{self, event, hackathon, location, EventType},
+    api_error::ApiError,
+    audit::{self, snapshot},
+    auth_guard::{self, RequirePermission},
+    base_types::{AppState, CreateResponse, DeleteResponse, GetResponse, UpdateResponse},
+    database,
+    docs::{partial, partial_schema},
+    occupancy::{self, EventOccupancy},
+    pagination::{Page, PageParams, SortOrder},
+    permissions::require,
+    prisma::{
+        self, event, hackathon, location, scan, AuditAction, EventType, PrismaClient, ScanType,
+    },
+    routes::hackathons::{find_hackathon, HackathonScope},
+    socket::{self, EventDeletedPayload, SocketEvent},
+    validation::{self, invalid_field, PartialChecks, ValidJson, ValidQuery},
 };
 
 impl<'__s> utoipa::ToSchema<'__s> for EventType {
@@ -54,13 +67,17 @@ pub struct EventEntity {
     _ws_skill_level: Option<String>,
     _ws_urls: Option<String>,
     _hackathon_id: Uuid,
+    _capacity: Option<i32>,
 }
 
-#[derive(serde::Deserialize, ToSchema)]
+#[derive(serde::Deserialize, ToSchema, Validate)]
 #[serde(rename_all = "camelCase")]
+#[validate(schema(function = "validate_event_times"))]
 pub struct CreateEventEntity {
+    #[validate(length(min = 1))]
     name: String,
     r#type: EventType,
+    #[validate(length(min = 1))]
     description: String,
     location_id: Uuid,
     icon: Option<String>,
@@ -71,6 +88,52 @@ pub struct CreateEventEntity {
     ws_skill_level: Option<String>,
     ws_urls: Option<String>,
     hackathon_id: Uuid,
+    /// How many people fit. Defaults to the location's capacity
+    #[validate(range(min = 1))]
+    capacity: Option<i32>,
+}
+
+fn validate_event_times(event: &CreateEventEntity) -> Result<(), ValidationError> {
+    validation::validate_time_range(&event.start_time, &event.end_time)
+}
+
+/// An event must happen during its hackathon. Checked by the handlers as it needs the database.
+async fn validate_event_window(
+    app_state: &AppState,
+    hackathon_id: String,
+    start_time: &chrono::DateTime<FixedOffset>,
+    end_time: &chrono::DateTime<FixedOffset>,
+) -> Result<(), ApiError> {
+    let hackathon = app_state
+        .client
+        .hackathon()
+        .find_unique(hackathon::UniqueWhereParam::IdEquals(hackathon_id))
+        .exec()
+        .await?
+        .ok_or_else(|| invalid_field("hackathonId", "not_found", "No hackathon with this id"))?;
+
+    if *start_time < hackathon.start_time || *end_time > hackathon.end_time {
+        return Err(invalid_field(
+            "startTime",
+            "outside_hackathon",
+            "The event must take place during the hackathon",
+        ));
+    }
+    Ok(())
+}
+
+async fn validate_event_location(
+    app_state: &AppState,
+    location_id: String,
+) -> Result<(), ApiError> {
+    app_state
+        .client
+        .location()
+        .find_unique(location::UniqueWhereParam::IdEquals(location_id))
+        .exec()
+        .await?
+        .ok_or_else(|| invalid_field("locationId", "not_found", "No location with this id"))?;
+    Ok(())
 }
 
 #[debug_handler]
@@ -80,21 +143,28 @@ pub struct CreateEventEntity {
     responses(
         (status = 201, description = "Created a new event"),
         (status = 400, description = "Bad request"),
-        (status = 401, description = "Unauthorized")
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 422, description = "Invalid event")
     ),
     request_body = CreateEventEntity,
     security(
-        ("http" = ["Exec", "Tech", "Team"])
+        ("http" = ["event:write"])
     )
 )]
 pub async fn create_event(
     State(app_state): State<AppState>,
-    Extension(request_user): Extension<RequestUser>,
-    Json(body): Json<CreateEventEntity>,
+    RequirePermission(request_user, _): RequirePermission<require::EventWrite>,
+    ValidJson(body): ValidJson<CreateEventEntity>,
 ) -> CreateResponse {
-    if !permission_check(request_user, prisma::Role::Team, vec![]) {
-        return Err((StatusCode::UNAUTHORIZED, "Unauthorized".to_owned()));
-    }
+    validate_event_window(
+        &app_state,
+        body.hackathon_id.to_string(),
+        &body.start_time,
+        &body.end_time,
+    )
+    .await?;
+    validate_event_location(&app_state, body.location_id.to_string()).await?;
 
     match app_state
         .client
@@ -113,43 +183,153 @@ pub async fn create_event(
                 event::ws_relevant_skills::set(body.ws_relevant_skills),
                 event::ws_skill_level::set(body.ws_skill_level),
                 event::ws_urls::set(body.ws_urls),
+                event::capacity::set(body.capacity),
             ],
         )
         .exec()
         .await
     {
-        Ok(_event) => Ok((StatusCode::CREATED, ())),
-        Err(err) => Err((StatusCode::BAD_REQUEST, err.to_string())),
+        Ok(event) => {
+            audit::record(
+                &app_state,
+                &request_user,
+                AuditAction::Create,
+                "event",
+                event.id.clone(),
+                None,
+                snapshot(&event),
+            )
+            .await;
+            socket::broadcast(&app_state, &SocketEvent::EventCreated(event.into()));
+            Ok((StatusCode::CREATED, ()))
+        }
+        Err(err) => Err(err.into()),
     }
 }
 
+#[derive(serde::Deserialize, ToSchema, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum EventSort {
+    #[default]
+    StartTime,
+    Name,
+}
+
+#[derive(serde::Deserialize, IntoParams, Validate)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct EventParams {
+    #[param(value_type = Option<String>)]
+    r#type: Option<EventType>,
+    location_id: Option<String>,
+    /// Only events starting at or after this time
+    from: Option<chrono::DateTime<FixedOffset>>,
+    /// Only events starting before this time
+    to: Option<chrono::DateTime<FixedOffset>>,
+    /// startTime or name. Defaults to startTime
+    #[param(inline)]
+    sort: Option<EventSort>,
+}
+
 #[debug_handler]
 #[utoipa::path(
     get,
     context_path = "/events",
     path = "",
     responses(
-        (status = 200, description = "Get all events", body = [EventEntity]),
+        (status = 200, description = "Get a page of events", body = EventPage),
         (status = 400, description = "Bad request"),
+        (status = 422, description = "Invalid query parameters"),
     ),
+    params(PageParams, HackathonScope, EventParams),
     security(
         ()
     )
 )]
 pub async fn get_all_events(
     State(app_state): State<AppState>,
-) -> GetResponse<Json<Vec<event::Data>>> {
-    match app_state
+    ValidQuery(page): ValidQuery<PageParams>,
+    ValidQuery(scope): ValidQuery<HackathonScope>,
+    ValidQuery(params): ValidQuery<EventParams>,
+) -> GetResponse<Json<Page<event::Data>>> {
+    let hackathon_id = scope.resolve(&app_state).await?;
+    let events = find_events_page(&app_state, hackathon_id, &page, params).await?;
+    Ok((StatusCode::OK, Json(events)))
+}
+
+#[debug_handler]
+#[utoipa::path(
+    get,
+    context_path = "/hackathons",
+    path = "/{id}/events",
+    responses(
+        (status = 200, description = "Get a page of a hackathon's events", body = EventPage),
+        (status = 400, description = "Bad request"),
+        (status = 404, description = "No hackathon found"),
+        (status = 422, description = "Invalid query parameters"),
+    ),
+    params(
+        ("id" = String, Path, description = "id of hackathon whose events to get"),
+        PageParams,
+        EventParams
+    ),
+    security(
+        ()
+    )
+)]
+pub async fn get_hackathon_events(
+    State(app_state): State<AppState>,
+    Path(id): Path<String>,
+    ValidQuery(page): ValidQuery<PageParams>,
+    ValidQuery(params): ValidQuery<EventParams>,
+) -> GetResponse<Json<Page<event::Data>>> {
+    let hackathon_id = find_hackathon(&app_state, id).await?.id;
+    let events = find_events_page(&app_state, hackathon_id, &page, params).await?;
+    Ok((StatusCode::OK, Json(events)))
+}
+
+async fn find_events_page(
+    app_state: &AppState,
+    hackathon_id: String,
+    page: &PageParams,
+    params: EventParams,
+) -> Result<Page<event::Data>, ApiError> {
+    let mut filters = vec![event::hackathon_id::equals(hackathon_id)];
+    if let Some(r#type) = params.r#type {
+        filters.push(event::r#type::equals(r#type));
+    }
+    if let Some(location_id) = params.location_id {
+        filters.push(event::location_id::equals(location_id));
+    }
+    if let Some(from) = params.from {
+        filters.push(event::start_time::gte(from));
+    }
+    if let Some(to) = params.to {
+        filters.push(event::start_time::lt(to));
+    }
+
+    let direction = || page.direction(SortOrder::Asc);
+    let sort = match params.sort.unwrap_or_default() {
+        EventSort::StartTime => event::start_time::order(direction()),
+        EventSort::Name => event::name::order(direction()),
+    };
+    let mut query = app_state
         .client
         .event()
-        .find_many(vec![])
-        .with(event::scan::fetch(vec![]))
-        .exec()
-        .await
-    {
-        Ok(events) => Ok((StatusCode::OK, Json(events))),
-        Err(err) => Err((StatusCode::BAD_REQUEST, err.to_string())),
+        .find_many(filters.clone())
+        .order_by(sort)
+        .order_by(event::id::order(direction()))
+        .skip(page.skip())
+        .take(page.take());
+    if let Some(cursor) = page.cursor.clone() {
+        query = query.cursor(event::UniqueWhereParam::IdEquals(cursor));
     }
+
+    let (events, total) = app_state
+        .client
+        ._batch((query, app_state.client.event().count(filters)))
+        .await?;
+    Ok(page.page(events, total, |event| event.id.clone()))
 }
 
 #[debug_handler]
@@ -179,9 +359,9 @@ pub async fn get_event_by_id(
     {
         Ok(event) => match event {
             Some(event) => Ok((StatusCode::OK, Json(event))),
-            None => Err((StatusCode::NOT_FOUND, "No event found".to_owned())),
+            None => Err(ApiError::NotFound("No event found".to_owned())),
         },
-        Err(err) => Err((StatusCode::BAD_REQUEST, err.to_string())),
+        Err(err) => Err(err.into()),
     }
 }
 
@@ -194,14 +374,16 @@ pub async fn get_event_by_id(
         (status = 204, description = "Deleted event by id"),
         (status = 400, description = "Bad request"),
         (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
     ),
     security(
-        ("http" = ["Exec", "Tech", "Team"])
+        ("http" = ["event:write"])
     )
 )]
 pub async fn delete_event_by_id(
     State(app_state): State<AppState>,
     Path(event_id): Path<Uuid>,
+    RequirePermission(request_user, _): RequirePermission<require::EventWrite>,
 ) -> DeleteResponse {
     match app_state
         .client
@@ -210,68 +392,438 @@ pub async fn delete_event_by_id(
         .exec()
         .await
     {
-        Ok(_) => Ok((StatusCode::NO_CONTENT, ())),
-        Err(err) => Err((StatusCode::BAD_REQUEST, err.to_string())),
+        Ok(event) => {
+            audit::record(
+                &app_state,
+                &request_user,
+                AuditAction::Delete,
+                "event",
+                event.id.clone(),
+                snapshot(&event),
+                None,
+            )
+            .await;
+            socket::broadcast(
+                &app_state,
+                &SocketEvent::EventDeleted(EventDeletedPayload {
+                    id: event.id,
+                    hackathon_id: event.hackathon_id,
+                }),
+            );
+            Ok((StatusCode::NO_CONTENT, ()))
+        }
+        Err(err) => Err(err.into()),
     }
 }
 
-#[derive(serde::Deserialize, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct CheckInUserToEventEntity {
-    hackathon_id: Uuid,
-    organizer_id: Uuid,
+event::partial_unchecked!(
+    EventUpdateEntity {
+        name
+        r#type
+        description
+        location_id
+        icon
+        start_time
+        end_time
+        ws_presenter_names
+        ws_relevant_skills
+        ws_skill_level
+        ws_urls
+        capacity
+    }
+);
+
+// The times and location are checked by the handler, as they need the stored event
+impl Validate for EventUpdateEntity {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        PartialChecks::new()
+            .non_empty("name", self.name.as_ref())
+            .non_empty("description", self.description.as_ref())
+            .check(
+                "capacity",
+                "range",
+                !matches!(self.capacity, Some(Some(capacity)) if capacity < 1),
+            )
+            .finish()
+    }
 }
 
+partial_schema!(EventUpdateEntity {
+    "name": partial::string(),
+    "type": utoipa::openapi::Ref::from_schema_name("EventType"),
+    "description": partial::string(),
+    "locationId": partial::string(),
+    "icon": partial::string(),
+    "startTime": partial::date_time(),
+    "endTime": partial::date_time(),
+    "wsPresenterNames": partial::string(),
+    "wsRelevantSkills": partial::string(),
+    "wsSkillLevel": partial::string(),
+    "wsUrls": partial::string(),
+    "capacity": partial::integer(),
+});
+
 #[debug_handler]
 #[utoipa::path(
-    post,
+    patch,
     context_path = "/events",
-    path = "/:event_id/check-in/user/:user_id",
+    path = "/:event_id",
     responses(
-        (status = 200, description = "Checked in user to event"),
+        (status = 200, description = "Updated the given fields of event by id"),
         (status = 400, description = "Bad request"),
         (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "No event found"),
+        (status = 422, description = "Invalid event"),
     ),
-    request_body = CheckInUserToEventEntity,
+    request_body = EventUpdateEntity,
     security(
-        ()
+        ("http" = ["event:write"])
     )
 )]
-pub async fn check_in_user_to_event(
+pub async fn update_event_by_id(
     State(app_state): State<AppState>,
-    Path((event_id, registration_id)): Path<(Uuid, Uuid)>,
-    Extension(request_user): Extension<RequestUser>,
-    Json(body): Json<CheckInUserToEventEntity>,
-) -> CreateResponse {
-    if !permission_check(request_user, prisma::Role::Team, vec![]) {
-        return Err((StatusCode::UNAUTHORIZED, "Unauthorized".to_owned()));
+    Path(event_id): Path<Uuid>,
+    RequirePermission(request_user, _): RequirePermission<require::EventWrite>,
+    ValidJson(body): ValidJson<EventUpdateEntity>,
+) -> UpdateResponse {
+    let before = match app_state
+        .client
+        .event()
+        .find_unique(event::UniqueWhereParam::IdEquals(event_id.to_string()))
+        .exec()
+        .await
+    {
+        Ok(Some(event)) => event,
+        Ok(None) => return Err(ApiError::NotFound("No event found".to_owned())),
+        Err(err) => return Err(err.into()),
+    };
+
+    let start_time = body.start_time.as_ref().unwrap_or(&before.start_time);
+    let end_time = body.end_time.as_ref().unwrap_or(&before.end_time);
+    validation::validate_time_range(start_time, end_time)?;
+    validate_event_window(
+        &app_state,
+        before.hackathon_id.clone(),
+        start_time,
+        end_time,
+    )
+    .await?;
+    if let Some(location_id) = &body.location_id {
+        validate_event_location(&app_state, location_id.clone()).await?;
     }
+
     match app_state
         .client
-        .scan()
-        .create(
-            prisma::registration::UniqueWhereParam::IdEquals(registration_id.to_string()),
-            prisma::user::UniqueWhereParam::IdEquals(body.organizer_id.to_string()),
-            body.hackathon_id.to_string(),
+        .event()
+        .update_unchecked(
             event::UniqueWhereParam::IdEquals(event_id.to_string()),
-            vec![],
+            body.to_params(),
         )
         .exec()
         .await
     {
-        Ok(_) => Ok((StatusCode::OK, ())),
-        Err(err) => Err((StatusCode::BAD_REQUEST, err.to_string())),
+        Ok(after) => {
+            audit::record(
+                &app_state,
+                &request_user,
+                AuditAction::Update,
+                "event",
+                after.id.clone(),
+                snapshot(&before),
+                snapshot(&after),
+            )
+            .await;
+            socket::broadcast(&app_state, &SocketEvent::EventUpdated(after.into()));
+            Ok((StatusCode::OK, ()))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(serde::Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+#[validate(schema(function = "validate_scan_location"))]
+pub struct CheckInUserToEventEntity {
+    /// Defaults to CHECK_IN
+    r#type: Option<ScanType>,
+    /// Checks in even when the event is full, which the response then warns about
+    #[serde(default)]
+    allow_over_capacity: bool,
+    /// Identifies the scanner, to tell devices apart in the scan log
+    #[validate(length(min = 1, max = 100))]
+    device_id: Option<String>,
+    /// Where the scan happened. Given together with `longitude`
+    #[validate(range(min = -90.0, max = 90.0))]
+    latitude: Option<f64>,
+    #[validate(range(min = -180.0, max = 180.0))]
+    longitude: Option<f64>,
+    #[validate(length(max = 191))]
+    notes: Option<String>,
+}
+
+fn validate_scan_location(body: &CheckInUserToEventEntity) -> Result<(), ValidationError> {
+    match (body.latitude, body.longitude) {
+        (Some(_), None) => Err(validation::field_error(
+            "longitude",
+            "incomplete_location",
+            "longitude must be given with latitude",
+        )),
+        (None, Some(_)) => Err(validation::field_error(
+            "latitude",
+            "incomplete_location",
+            "latitude must be given with longitude",
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// What the volunteer's screen shows about the scanned hacker.
+#[derive(serde::Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScannedHackerEntity {
+    first_name: String,
+    last_name: String,
+    dietary_restrictions: Option<String>,
+    allergies: Option<String>,
+}
+
+#[derive(serde::Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanResultEntity {
+    r#type: ScanType,
+    /// When the hacker was scanned. For a repeat scan, when the scan it repeated was made, which is
+    /// left out for scans recorded before scan times were tracked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scanned_at: Option<chrono::DateTime<FixedOffset>>,
+    /// Set when this repeated the hacker's latest scan at the event, in which case nothing was
+    /// recorded
+    duplicate: bool,
+    hacker: ScannedHackerEntity,
+    /// The event's occupancy after the scan
+    occupancy: EventOccupancy,
+    /// Set when the event was already full and `allowOverCapacity` let the check in through
+    over_capacity: bool,
+}
+
+/// A scan once it's been recorded, or the latest one if it repeated it.
+struct RecordedScan {
+    scan: scan::Data,
+    duplicate: bool,
+    over_capacity: bool,
+}
+
+/// Records a scan unless it repeats the hacker's latest one at the event.
+///
+/// The event is locked first, so simultaneous scans at the door are recorded one at a time and
+/// none can pass the capacity check on an occupancy another is about to change.
+async fn record_scan(
+    client: PrismaClient,
+    event_id: String,
+    hackathon_id: String,
+    registration_id: String,
+    organizer_id: String,
+    body: CheckInUserToEventEntity,
+) -> Result<RecordedScan, ApiError> {
+    let scan_type = body.r#type.unwrap_or(ScanType::CheckIn);
+    database::lock_for_update(&client, "Event", event_id.clone()).await?;
+
+    match occupancy::latest_scan(&client, event_id.clone(), registration_id.clone()).await? {
+        Some(scan) if scan.r#type == scan_type => {
+            return Ok(RecordedScan {
+                scan,
+                duplicate: true,
+                over_capacity: false,
+            })
+        }
+        None if scan_type == ScanType::CheckOut => {
+            return Err(invalid_field(
+                "type",
+                "not_checked_in",
+                "Can't check out before checking in",
+            ))
+        }
+        _ => {}
+    }
+
+    let over_capacity = scan_type == ScanType::CheckIn
+        && occupancy::event_occupancy(&client, event_id.clone())
+            .await?
+            .is_full();
+    if over_capacity && !body.allow_over_capacity {
+        return Err(ApiError::Conflict("The event is full".to_owned()));
+    }
+
+    let scan = client
+        .scan()
+        .create(
+            prisma::registration::UniqueWhereParam::IdEquals(registration_id),
+            prisma::user::UniqueWhereParam::IdEquals(organizer_id),
+            hackathon_id,
+            event::UniqueWhereParam::IdEquals(event_id),
+            vec![
+                scan::r#type::set(scan_type),
+                scan::device_id::set(body.device_id),
+                scan::latitude::set(body.latitude),
+                scan::longitude::set(body.longitude),
+                scan::notes::set(body.notes),
+            ],
+        )
+        .exec()
+        .await?;
+    Ok(RecordedScan {
+        scan,
+        duplicate: false,
+        over_capacity,
+    })
+}
+
+/// Scans a hacker in to or out of an event, as the signed in organizer.
+///
+/// Scans are kept as a log, so a hacker can come and go as often as they like. Repeating their
+/// latest scan isn't an error, as volunteers rescan when they're unsure it worked; that scan is
+/// returned instead, without recording another one.
+#[debug_handler]
+#[utoipa::path(
+    post,
+    context_path = "/events",
+    path = "/{event_id}/check-in/user/{registration_id}",
+    responses(
+        (status = 200, description = "Checked the user in to or out of the event, or had already", body = ScanResultEntity),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Event or registration not found"),
+        (status = 409, description = "The event is full"),
+        (status = 422, description = "Invalid check in"),
+    ),
+    params(
+        ("event_id" = String, Path, description = "id of event to check in to or out of"),
+        ("registration_id" = String, Path, description = "id of the hacker's registration")
+    ),
+    request_body = CheckInUserToEventEntity,
+    security(
+        ("http" = ["event:write"])
+    )
+)]
+pub async fn check_in_user_to_event(
+    State(app_state): State<AppState>,
+    Path((event_id, registration_id)): Path<(Uuid, Uuid)>,
+    RequirePermission(request_user, _): RequirePermission<require::EventWrite>,
+    ValidJson(body): ValidJson<CheckInUserToEventEntity>,
+) -> GetResponse<Json<ScanResultEntity>> {
+    let (event_id, registration_id) = (event_id.to_string(), registration_id.to_string());
+
+    let event = app_state
+        .client
+        .event()
+        .find_unique(event::UniqueWhereParam::IdEquals(event_id.clone()))
+        .exec()
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Event not found".to_owned()))?;
+    let registration = app_state
+        .client
+        .registration()
+        .find_unique(prisma::registration::UniqueWhereParam::IdEquals(
+            registration_id.clone(),
+        ))
+        .with(prisma::registration::user::fetch())
+        .exec()
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Registration not found".to_owned()))?;
+    if registration.hackathon_id != event.hackathon_id {
+        return Err(invalid_field(
+            "registrationId",
+            "wrong_hackathon",
+            "The registration is for a different hackathon than the event",
+        ));
+    }
+    let user = registration
+        .user()
+        .map_err(|err| ApiError::Internal(err.to_string()))?;
+    let hacker = ScannedHackerEntity {
+        first_name: user.first_name.clone(),
+        last_name: user.last_name.clone(),
+        dietary_restrictions: registration.dietary_restrictions.clone(),
+        allergies: registration.allergies.clone(),
+    };
+
+    let RecordedScan {
+        scan,
+        duplicate,
+        over_capacity,
+    } = app_state
+        .client
+        ._transaction()
+        .run(|client| {
+            record_scan(
+                client,
+                event_id.clone(),
+                event.hackathon_id,
+                registration_id,
+                request_user.id,
+                body,
+            )
+        })
+        .await?;
+    let occupancy = occupancy::event_occupancy(&app_state.client, event_id).await?;
+    if !duplicate {
+        socket::broadcast(&app_state, &SocketEvent::ScanCreated(scan.clone().into()));
+        socket::broadcast(
+            &app_state,
+            &SocketEvent::OccupancyChanged(occupancy.clone()),
+        );
     }
+    Ok((
+        StatusCode::OK,
+        Json(ScanResultEntity {
+            r#type: scan.r#type,
+            scanned_at: scan.created_at,
+            duplicate,
+            hacker,
+            occupancy,
+            over_capacity,
+        }),
+    ))
+}
+
+#[debug_handler]
+#[utoipa::path(
+    get,
+    context_path = "/events",
+    path = "/:event_id/occupancy",
+    responses(
+        (status = 200, description = "How many people are at the event", body = EventOccupancy),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Event not found"),
+    ),
+    security(
+        ("http" = ["event:write"])
+    )
+)]
+pub async fn get_event_occupancy(
+    State(app_state): State<AppState>,
+    Path(event_id): Path<Uuid>,
+    _: RequirePermission<require::EventWrite>,
+) -> GetResponse<Json<EventOccupancy>> {
+    let occupancy = occupancy::event_occupancy(&app_state.client, event_id.to_string()).await?;
+    Ok((StatusCode::OK, Json(occupancy)))
 }
 
 pub fn events_get_router(app_state: AppState) -> Router {
     Router::new()
         .route("/", post(create_event))
-        .route("/:event_id", delete(delete_event_by_id))
         .route(
-            "/:event_id/check-in/user/:user_id",
+            "/:event_id",
+            patch(update_event_by_id).delete(delete_event_by_id),
+        )
+        .route(
+            "/:event_id/check-in/user/:registration_id",
             post(check_in_user_to_event),
         )
+        .route("/:event_id/occupancy", get(get_event_occupancy))
         .route_layer(middleware::from_fn_with_state(
             app_state.clone(),
             auth_guard::require_auth,
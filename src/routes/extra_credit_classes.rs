@@ -1,39 +1,70 @@
 use axum::{
     extract::{Path, State},
-    response::Response,
-    routing::get,
+    middleware,
+    routing::{get, patch, post},
     Json, Router,
 };
 use hyper::StatusCode;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 use uuid::Uuid;
+use validator::{Validate, ValidationErrors};
 
 use crate::{
-    base_types::AppState,
-    prisma::{self, extra_credit_class::Data},
+    api_error::ApiError,
+    audit::{self, snapshot},
+    auth_guard::{self, RequirePermission},
+    base_types::{AppState, CreateResponse, UpdateResponse},
+    docs::{partial, partial_schema},
+    pagination::{Page, PageParams, SortOrder},
+    permissions::require,
+    prisma::{
+        self,
+        extra_credit_class::{self, Data},
+        AuditAction,
+    },
+    routes::hackathons::{find_hackathon, HackathonScope},
+    validation::{invalid_field, PartialChecks, ValidJson, ValidQuery},
 };
 
-#[derive(Serialize, Deserialize, ToSchema)]
+#[derive(Serialize, Deserialize, ToSchema, Validate)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateExtraCreditClassEntity {
+    #[validate(length(min = 1))]
     name: String,
     hackathon_id: Uuid,
 }
 #[axum::debug_handler]
 #[utoipa::path(
     post,
-    path = "/extra_credit_class",
+    path = "/extra_credit/classes",
     responses(
-        (status = 200, description = "Create a new extra credit class", body = String),
-        (status = 400, description = "Bad request")
+        (status = 201, description = "Created a new extra credit class"),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 422, description = "Invalid extra credit class")
     ),
-    request_body = CreateExtraCreditClassEntity
+    request_body = CreateExtraCreditClassEntity,
+    security(
+        ("api_key" = ["hackathon:write"])
+    )
 )]
 async fn create_extra_credit_class(
     State(app_state): State<AppState>,
-    Json(body): Json<CreateExtraCreditClassEntity>,
-) -> Result<Response<String>, StatusCode> {
+    RequirePermission(request_user, _): RequirePermission<require::HackathonWrite>,
+    ValidJson(body): ValidJson<CreateExtraCreditClassEntity>,
+) -> CreateResponse {
+    app_state
+        .client
+        .hackathon()
+        .find_unique(prisma::hackathon::UniqueWhereParam::IdEquals(
+            body.hackathon_id.to_string(),
+        ))
+        .exec()
+        .await?
+        .ok_or_else(|| invalid_field("hackathonId", "not_found", "No hackathon with this id"))?;
+
     match app_state
         .client
         .extra_credit_class()
@@ -45,10 +76,20 @@ async fn create_extra_credit_class(
         .exec()
         .await
     {
-        Ok(_hackathon) => Ok(Response::new(
-            "Created extra credit class successfully".to_owned(),
-        )),
-        Err(_err) => Err(StatusCode::BAD_REQUEST),
+        Ok(extra_credit_class) => {
+            audit::record(
+                &app_state,
+                &request_user,
+                AuditAction::Create,
+                "extra_credit_class",
+                extra_credit_class.id.clone(),
+                None,
+                snapshot(&extra_credit_class),
+            )
+            .await;
+            Ok((StatusCode::CREATED, ()))
+        }
+        Err(err) => Err(err.into()),
     }
 }
 #[axum::debug_handler]
@@ -56,23 +97,78 @@ async fn create_extra_credit_class(
     get,
     path = "/extra_credit/classes",
     responses(
-        (status = 200, description = "Get all extra credit classes"),
-        (status = 400, description = "Bad request")
-    )
+        (status = 200, description = "Get a page of extra credit classes, by name"),
+        (status = 400, description = "Bad request"),
+        (status = 422, description = "Invalid query parameters")
+    ),
+    params(PageParams, HackathonScope)
 )]
 async fn get_all_extra_credit_classes(
     State(app_state): State<AppState>,
-) -> Result<Json<Vec<Data>>, StatusCode> {
-    match app_state
+    ValidQuery(page): ValidQuery<PageParams>,
+    ValidQuery(scope): ValidQuery<HackathonScope>,
+) -> Result<Json<Page<Data>>, ApiError> {
+    let hackathon_id = scope.resolve(&app_state).await?;
+    Ok(Json(
+        find_extra_credit_classes_page(&app_state, hackathon_id, &page).await?,
+    ))
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    get,
+    path = "/hackathons/{id}/extra_credit/classes",
+    responses(
+        (status = 200, description = "Get a page of a hackathon's extra credit classes, by name"),
+        (status = 400, description = "Bad request"),
+        (status = 404, description = "No hackathon found"),
+        (status = 422, description = "Invalid query parameters")
+    ),
+    params(
+        ("id" = String, Path, description = "id of hackathon whose extra credit classes to get"),
+        PageParams
+    )
+)]
+pub async fn get_hackathon_extra_credit_classes(
+    State(app_state): State<AppState>,
+    Path(id): Path<String>,
+    ValidQuery(page): ValidQuery<PageParams>,
+) -> Result<Json<Page<Data>>, ApiError> {
+    let hackathon_id = find_hackathon(&app_state, id).await?.id;
+    Ok(Json(
+        find_extra_credit_classes_page(&app_state, hackathon_id, &page).await?,
+    ))
+}
+
+async fn find_extra_credit_classes_page(
+    app_state: &AppState,
+    hackathon_id: String,
+    page: &PageParams,
+) -> Result<Page<Data>, ApiError> {
+    let filters = vec![extra_credit_class::hackathon_id::equals(hackathon_id)];
+
+    let direction = || page.direction(SortOrder::Asc);
+    let mut query = app_state
         .client
         .extra_credit_class()
-        .find_many(vec![])
-        .exec()
-        .await
-    {
-        Ok(extra_credit_classes) => Ok(Json(extra_credit_classes)),
-        Err(_err) => Err(StatusCode::BAD_REQUEST),
+        .find_many(filters.clone())
+        .order_by(extra_credit_class::name::order(direction()))
+        .order_by(extra_credit_class::id::order(direction()))
+        .skip(page.skip())
+        .take(page.take());
+    if let Some(cursor) = page.cursor.clone() {
+        query = query.cursor(extra_credit_class::UniqueWhereParam::IdEquals(cursor));
     }
+
+    let (extra_credit_classes, total) = app_state
+        .client
+        ._batch((query, app_state.client.extra_credit_class().count(filters)))
+        .await?;
+    Ok(
+        page.page(extra_credit_classes, total, |extra_credit_class| {
+            extra_credit_class.id.clone()
+        }),
+    )
 }
 #[axum::debug_handler]
 #[utoipa::path(
@@ -86,7 +182,7 @@ async fn get_all_extra_credit_classes(
 async fn get_extra_credit_class_by_id(
     State(app_state): State<AppState>,
     Path(id): Path<String>,
-) -> Result<Json<Data>, (StatusCode, String)> {
+) -> Result<Json<Data>, ApiError> {
     match app_state
         .client
         .extra_credit_class()
@@ -96,9 +192,91 @@ async fn get_extra_credit_class_by_id(
     {
         Ok(extra_credit_class) => match extra_credit_class {
             Some(extra_credit_class) => Ok(Json(extra_credit_class)),
-            None => Err((StatusCode::NOT_FOUND, "Not FOund".to_owned())),
+            None => Err(ApiError::NotFound(
+                "Extra credit class not found".to_owned(),
+            )),
         },
-        Err(err) => Err((StatusCode::BAD_REQUEST, err.to_string())),
+        Err(err) => Err(err.into()),
+    }
+}
+
+extra_credit_class::partial_unchecked!(ExtraCreditClassUpdateEntity { name });
+
+impl Validate for ExtraCreditClassUpdateEntity {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        PartialChecks::new()
+            .non_empty("name", self.name.as_ref())
+            .finish()
+    }
+}
+
+partial_schema!(ExtraCreditClassUpdateEntity {
+    "name": partial::string(),
+});
+
+#[axum::debug_handler]
+#[utoipa::path(
+    patch,
+    path = "/extra_credit/classes/{id}",
+    responses(
+        (status = 200, description = "Updated the given fields of a extra credit class by id"),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Extra credit class not found"),
+        (status = 422, description = "Invalid extra credit class")
+    ),
+    request_body = ExtraCreditClassUpdateEntity,
+    security(
+        ("api_key" = ["hackathon:write"])
+    )
+)]
+async fn update_extra_credit_class_by_id(
+    State(app_state): State<AppState>,
+    Path(id): Path<String>,
+    RequirePermission(request_user, _): RequirePermission<require::HackathonWrite>,
+    ValidJson(body): ValidJson<ExtraCreditClassUpdateEntity>,
+) -> UpdateResponse {
+    let before = match app_state
+        .client
+        .extra_credit_class()
+        .find_unique(extra_credit_class::UniqueWhereParam::IdEquals(id.clone()))
+        .exec()
+        .await
+    {
+        Ok(Some(extra_credit_class)) => extra_credit_class,
+        Ok(None) => {
+            return Err(ApiError::NotFound(
+                "Extra credit class not found".to_owned(),
+            ))
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    match app_state
+        .client
+        .extra_credit_class()
+        .update_unchecked(
+            extra_credit_class::UniqueWhereParam::IdEquals(id),
+            body.to_params(),
+        )
+        .exec()
+        .await
+    {
+        Ok(after) => {
+            audit::record(
+                &app_state,
+                &request_user,
+                AuditAction::Update,
+                "extra_credit_class",
+                after.id.clone(),
+                snapshot(&before),
+                snapshot(&after),
+            )
+            .await;
+            Ok((StatusCode::OK, ()))
+        }
+        Err(err) => Err(err.into()),
     }
 }
 
@@ -114,7 +292,7 @@ async fn get_extra_credit_class_by_id(
 async fn delete_extra_credit_class_by_id(
     State(app_state): State<AppState>,
     Path(id): Path<String>,
-) -> Result<StatusCode, (StatusCode, String)> {
+) -> Result<StatusCode, ApiError> {
     match app_state
         .client
         .extra_credit_class()
@@ -123,16 +301,19 @@ async fn delete_extra_credit_class_by_id(
         .await
     {
         Ok(_) => Ok(StatusCode::NO_CONTENT),
-        Err(err) => Err((StatusCode::BAD_REQUEST, err.to_string())),
+        Err(err) => Err(err.into()),
     }
 }
 
 pub fn extra_credit_class_get_router(app_state: AppState) -> Router {
     Router::new()
-        .route(
-            "/",
-            get(get_all_extra_credit_classes).post(create_extra_credit_class),
-        )
+        .route("/", post(create_extra_credit_class))
+        .route("/:id", patch(update_extra_credit_class_by_id))
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            auth_guard::require_auth,
+        ))
+        .route("/", get(get_all_extra_credit_classes))
         .route(
             "/:id",
             get(get_extra_credit_class_by_id).delete(delete_extra_credit_class_by_id),
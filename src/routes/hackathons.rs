@@ -1,29 +1,52 @@
 use std::vec;
 
 use axum::{
-    extract::{Extension, Path, Query, State},
+    extract::{Path, State},
     middleware,
     routing::{get, patch, post},
     Json, Router,
 };
+use chrono::{DateTime, FixedOffset};
 use hyper::StatusCode;
-use utoipa::IntoParams;
+use prisma_client_rust::operator::or;
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
 
 use crate::{
-    auth_guard::{self, permission_check, RequestUser},
+    api_error::{ApiError, FieldError},
+    audit::{self, snapshot},
+    auth_guard::{self, RequirePermission},
     base_types::AppState,
     base_types::{CreateResponse, DeleteResponse, GetResponse, UpdateResponse},
-    entities::hackathons::CreateHackathonEntity,
+    entities::hackathons::{CreateHackathonEntity, HackathonUpdateEntity},
+    pagination::{Page, PageParams, SortOrder},
+    permissions::require,
     prisma::{
+        event,
         hackathon::{self, Data, UniqueWhereParam},
-        location, EventType, Role,
+        location, AuditAction, EventType,
     },
+    routes::{events, extra_credit_classes, scans, sponsors},
+    socket::{self, SocketEvent},
+    validation::{validate_time_range, ValidJson, ValidQuery},
 };
 
-#[derive(serde::Deserialize, IntoParams)]
+#[derive(serde::Deserialize, ToSchema, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+enum HackathonSort {
+    #[default]
+    StartTime,
+    Name,
+}
+
+#[derive(serde::Deserialize, IntoParams, Validate)]
+#[into_params(parameter_in = Query)]
 struct Params {
     #[serde(default)]
     active: Option<bool>,
+    /// startTime or name. Defaults to startTime, newest first
+    #[param(inline)]
+    sort: Option<HackathonSort>,
 }
 
 #[axum::debug_handler]
@@ -34,21 +57,20 @@ struct Params {
     responses(
         (status = 201, description = "Created a new hackathon"),
         (status = 400, description = "Bad request"),
-        (status = 401, description = "Unauthorized")
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 422, description = "Invalid hackathon")
     ),
     request_body = CreateHackathonEntity,
     security(
-        ("api_key" = ["Exec", "Tech"])
+        ("api_key" = ["hackathon:write"])
     )
 )]
 async fn create_hackathon(
     State(app_state): State<AppState>,
-    Extension(request_user): Extension<RequestUser>,
-    Json(body): Json<CreateHackathonEntity>,
+    RequirePermission(request_user, _): RequirePermission<require::HackathonWrite>,
+    ValidJson(body): ValidJson<CreateHackathonEntity>,
 ) -> CreateResponse {
-    if !permission_check(request_user, Role::Exec, vec![]) {
-        return Err((StatusCode::UNAUTHORIZED, "Unauthorized".to_owned()));
-    }
     match app_state
         .client
         .hackathon()
@@ -57,6 +79,17 @@ async fn create_hackathon(
         .await
     {
         Ok(hackathon) => {
+            audit::record(
+                &app_state,
+                &request_user,
+                AuditAction::Create,
+                "hackathon",
+                hackathon.id.clone(),
+                None,
+                snapshot(&hackathon),
+            )
+            .await;
+
             let event = app_state
                 .client
                 .event()
@@ -75,10 +108,10 @@ async fn create_hackathon(
 
             match event {
                 Ok(_) => Ok((StatusCode::CREATED, ())),
-                Err(err) => Err((StatusCode::INTERNAL_SERVER_ERROR, err.to_string())),
+                Err(err) => Err(err.into()),
             }
         }
-        Err(err) => Err((StatusCode::INTERNAL_SERVER_ERROR, err.to_string())),
+        Err(err) => Err(err.into()),
     }
 }
 
@@ -88,40 +121,55 @@ async fn create_hackathon(
     context_path = "/hackathons",
     path = "",
     responses(
-        (status = 200, description = "Returns all hackathons", body = [HackathonEntity]),
+        (status = 200, description = "Returns a page of hackathons", body = HackathonPage),
         (status = 400, description = "Bad request"),
         (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 422, description = "Invalid query parameters"),
     ),
-    params(Params),
+    params(PageParams, Params),
     security(
-                ("api_key" = ["Exec", "Tech"])
+                ("api_key" = ["hackathon:read"])
     )
 )]
 async fn get_all_hackathon(
     State(app_state): State<AppState>,
-    Query(params): Query<Params>,
-    Extension(request_user): Extension<RequestUser>,
-) -> GetResponse<Json<Vec<Data>>> {
-    if !permission_check(request_user, Role::Team, vec![]) {
-        return Err((StatusCode::UNAUTHORIZED, "Unauthorized".to_owned()));
+    ValidQuery(page): ValidQuery<PageParams>,
+    ValidQuery(params): ValidQuery<Params>,
+    _: RequirePermission<require::HackathonRead>,
+) -> GetResponse<Json<Page<Data>>> {
+    let mut filters = vec![];
+    if let Some(active) = params.active {
+        filters.push(hackathon::active::equals(active));
     }
 
-    if params.active.is_some() {
-        match app_state
-            .client
-            .hackathon()
-            .find_many(vec![hackathon::active::equals(params.active.unwrap())])
-            .exec()
-            .await
-        {
-            Ok(hackathons) => Ok((StatusCode::OK, Json(hackathons))),
-            Err(err) => Err((StatusCode::INTERNAL_SERVER_ERROR, err.to_string())),
-        }
-    } else {
-        match app_state.client.hackathon().find_many(vec![]).exec().await {
-            Ok(hackathons) => Ok((StatusCode::OK, Json(hackathons))),
-            Err(err) => Err((StatusCode::INTERNAL_SERVER_ERROR, err.to_string())),
-        }
+    let direction = || page.direction(SortOrder::Desc);
+    let sort = match params.sort.unwrap_or_default() {
+        HackathonSort::StartTime => hackathon::start_time::order(direction()),
+        HackathonSort::Name => hackathon::name::order(direction()),
+    };
+    let mut query = app_state
+        .client
+        .hackathon()
+        .find_many(filters.clone())
+        .order_by(sort)
+        .order_by(hackathon::id::order(direction()))
+        .skip(page.skip())
+        .take(page.take());
+    if let Some(cursor) = page.cursor.clone() {
+        query = query.cursor(UniqueWhereParam::IdEquals(cursor));
+    }
+
+    match app_state
+        .client
+        ._batch((query, app_state.client.hackathon().count(filters)))
+        .await
+    {
+        Ok((hackathons, total)) => Ok((
+            StatusCode::OK,
+            Json(page.page(hackathons, total, |hackathon| hackathon.id.clone())),
+        )),
+        Err(err) => Err(err.into()),
     }
 }
 
@@ -134,67 +182,153 @@ async fn get_all_hackathon(
         (status = 200, description = "Returns hackathon with id", body = HackathonEntity),
         (status = 400, description = "Bad request"),
         (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
         (status = 404, description = "No hackathon found")
     ),
     params(("id" = String, Path, description = "id of hackathon to get")),
     security(
-                ("api_key" = ["Exec", "Tech"])
+                ("api_key" = ["hackathon:read"])
     )
 )]
 async fn get_hackathon_by_id(
     State(app_state): State<AppState>,
     Path(id): Path<String>,
-    Extension(request_user): Extension<RequestUser>,
+    _: RequirePermission<require::HackathonRead>,
 ) -> GetResponse<Json<Data>> {
-    if !permission_check(request_user, Role::Team, vec![]) {
-        return Err((StatusCode::UNAUTHORIZED, "Unauthorized".to_owned()));
-    }
+    Ok((StatusCode::OK, Json(find_hackathon(&app_state, id).await?)))
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    delete,
+    context_path = "/hackathons",
+    path = "/{id}",
+    responses((status = 204, description = "Delete hackathon with id"),
+    (status = 400, description = "Bad request"),
+    (status = 401, description = "Unauthorized"),
+    (status = 403, description = "Forbidden"),
+    ),
+    params(("id" = String, Path, description = "id of hackathon to delete")),
+    security(
+                ("api_key" = ["hackathon:write"])
+    )
+)]
+async fn delete_hackathon_by_id(
+    State(app_state): State<AppState>,
+    Path(id): Path<String>,
+    RequirePermission(request_user, _): RequirePermission<require::HackathonWrite>,
+) -> DeleteResponse {
     match app_state
         .client
         .hackathon()
-        .find_unique(UniqueWhereParam::IdEquals(id))
+        .delete(UniqueWhereParam::IdEquals(id))
         .exec()
         .await
     {
-        Ok(hackathons) => match hackathons {
-            Some(hackathon) => Ok((StatusCode::OK, Json(hackathon))),
-            None => Err((StatusCode::NOT_FOUND, "No hackathon found".to_owned())),
-        },
-        Err(err) => Err((StatusCode::INTERNAL_SERVER_ERROR, err.to_string())),
+        Ok(hackathon) => {
+            audit::record(
+                &app_state,
+                &request_user,
+                AuditAction::Delete,
+                "hackathon",
+                hackathon.id.clone(),
+                snapshot(&hackathon),
+                None,
+            )
+            .await;
+            Ok((StatusCode::NO_CONTENT, ()))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Events must happen during their hackathon, so it can't be moved or shortened to leave any of
+/// them out. The error names those events, to move them first.
+async fn validate_events_inside(
+    app_state: &AppState,
+    id: String,
+    start_time: DateTime<FixedOffset>,
+    end_time: DateTime<FixedOffset>,
+) -> Result<(), ApiError> {
+    let outside = app_state
+        .client
+        .event()
+        .find_many(vec![
+            event::hackathon_id::equals(id),
+            or(vec![
+                event::start_time::lt(start_time),
+                event::end_time::gt(end_time),
+            ]),
+        ])
+        .exec()
+        .await?;
+    if outside.is_empty() {
+        return Ok(());
     }
+
+    let names: Vec<&str> = outside.iter().map(|event| event.name.as_str()).collect();
+    Err(ApiError::Validation(vec![FieldError {
+        field: "startTime".to_owned(),
+        code: "events_outside".to_owned(),
+        message: Some(format!(
+            "These events would no longer take place during the hackathon: {}",
+            names.join(", ")
+        )),
+    }]))
 }
 
 #[axum::debug_handler]
 #[utoipa::path(
-    delete,
+    patch,
     context_path = "/hackathons",
     path = "/{id}",
-    responses((status = 204, description = "Delete hackathon with id"),
+    responses((status = 200, description = "Updated the given fields of hackathon with id"),
     (status = 400, description = "Bad request"),
     (status = 401, description = "Unauthorized"),
+    (status = 403, description = "Forbidden"),
+    (status = 404, description = "No hackathon found"),
+    (status = 422, description = "Invalid hackathon"),
     ),
-    params(("id" = String, Path, description = "id of hackathon to delete")),
+    params(("id" = String, Path, description = "id of hackathon to update")),
+    request_body = HackathonUpdateEntity,
     security(
-                ("api_key" = ["Exec", "Tech"])
+                ("api_key" = ["hackathon:write"])
     )
 )]
-async fn delete_hackathon_by_id(
+async fn update_hackathon_by_id(
     State(app_state): State<AppState>,
     Path(id): Path<String>,
-    Extension(request_user): Extension<RequestUser>,
-) -> DeleteResponse {
-    if !permission_check(request_user, Role::Exec, vec![]) {
-        return Err((StatusCode::UNAUTHORIZED, "Unauthorized".to_owned()));
-    }
+    RequirePermission(request_user, _): RequirePermission<require::HackathonWrite>,
+    ValidJson(body): ValidJson<HackathonUpdateEntity>,
+) -> UpdateResponse {
+    let before = find_hackathon(&app_state, id.clone()).await?;
+
+    let start_time = body.start_time.unwrap_or(before.start_time);
+    let end_time = body.end_time.unwrap_or(before.end_time);
+    validate_time_range(&start_time, &end_time)?;
+    validate_events_inside(&app_state, id.clone(), start_time, end_time).await?;
+
     match app_state
         .client
         .hackathon()
-        .delete(UniqueWhereParam::IdEquals(id))
+        .update_unchecked(UniqueWhereParam::IdEquals(id), body.to_params())
         .exec()
         .await
     {
-        Ok(_) => Ok((StatusCode::NO_CONTENT, ())),
-        Err(err) => Err((StatusCode::BAD_REQUEST, err.to_string())),
+        Ok(after) => {
+            audit::record(
+                &app_state,
+                &request_user,
+                AuditAction::Update,
+                "hackathon",
+                after.id.clone(),
+                snapshot(&before),
+                snapshot(&after),
+            )
+            .await;
+            Ok((StatusCode::OK, ()))
+        }
+        Err(err) => Err(err.into()),
     }
 }
 
@@ -206,20 +340,32 @@ async fn delete_hackathon_by_id(
     responses((status = 200, description = "Set hackathon with id to active"),
     (status = 400, description = "Bad request"),
     (status = 401, description = "Unauthorized"),
+    (status = 403, description = "Forbidden"),
+    (status = 404, description = "No hackathon found"),
     ),
     params(("id" = String, Path, description = "id of hackathon to set active")),
     security(
-                ("api_key" = ["Exec", "Tech"]
+                ("api_key" = ["hackathon:write"]
     )
 ))]
 async fn set_active_hackathon(
     State(app_state): State<AppState>,
     Path(id): Path<String>,
-    Extension(request_user): Extension<RequestUser>,
+    RequirePermission(request_user, _): RequirePermission<require::HackathonWrite>,
 ) -> UpdateResponse {
-    if !permission_check(request_user, Role::Exec, vec![]) {
-        return Err((StatusCode::UNAUTHORIZED, "Unauthorized".to_owned()));
-    }
+    // Kept for the audit log, which records both the deactivated and the activated hackathon
+    let previously_active = match app_state
+        .client
+        .hackathon()
+        .find_many(vec![hackathon::active::equals(true)])
+        .exec()
+        .await
+    {
+        Ok(hackathons) => hackathons,
+        Err(err) => return Err(err.into()),
+    };
+    let before = find_hackathon(&app_state, id.clone()).await?;
+
     //set all hackathons to inactive
     match app_state
         .client
@@ -229,11 +375,11 @@ async fn set_active_hackathon(
         .await
     {
         Ok(_) => (),
-        Err(err) => return Err((StatusCode::BAD_REQUEST, err.to_string())),
+        Err(err) => return Err(err.into()),
     }
 
     //set hackathon with id to active
-    match app_state
+    let after = match app_state
         .client
         .hackathon()
         .update(
@@ -243,9 +389,43 @@ async fn set_active_hackathon(
         .exec()
         .await
     {
-        Ok(_) => Ok((StatusCode::OK, ())),
-        Err(err) => Err((StatusCode::BAD_REQUEST, err.to_string())),
+        Ok(hackathon) => hackathon,
+        Err(err) => return Err(err.into()),
+    };
+
+    for hackathon in previously_active {
+        if hackathon.id == after.id {
+            continue;
+        }
+        let mut deactivated = hackathon.clone();
+        deactivated.active = false;
+        audit::record(
+            &app_state,
+            &request_user,
+            AuditAction::Update,
+            "hackathon",
+            hackathon.id.clone(),
+            snapshot(&hackathon),
+            snapshot(&deactivated),
+        )
+        .await;
     }
+    audit::record(
+        &app_state,
+        &request_user,
+        AuditAction::Update,
+        "hackathon",
+        after.id.clone(),
+        snapshot(&before),
+        snapshot(&after),
+    )
+    .await;
+    socket::broadcast(
+        &app_state,
+        &SocketEvent::ActiveHackathonChanged(after.into()),
+    );
+
+    Ok((StatusCode::OK, ()))
 }
 
 #[axum::debug_handler]
@@ -271,9 +451,55 @@ async fn get_active_hackathon(State(app_state): State<AppState>) -> GetResponse<
     {
         Ok(hackathons) => match hackathons {
             Some(hackathon) => Ok((StatusCode::OK, Json(hackathon))),
-            None => Err((StatusCode::NOT_FOUND, "No hackathon found".to_owned())),
+            None => Err(ApiError::NotFound("No hackathon found".to_owned())),
         },
-        Err(err) => Err((StatusCode::INTERNAL_SERVER_ERROR, err.to_string())),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn find_hackathon(app_state: &AppState, id: String) -> Result<Data, ApiError> {
+    match app_state
+        .client
+        .hackathon()
+        .find_unique(UniqueWhereParam::IdEquals(id))
+        .exec()
+        .await
+    {
+        Ok(Some(hackathon)) => Ok(hackathon),
+        Ok(None) => Err(ApiError::NotFound("No hackathon found".to_owned())),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn find_active_hackathon(app_state: &AppState) -> Result<Data, ApiError> {
+    match app_state
+        .client
+        .hackathon()
+        .find_first(vec![hackathon::active::equals(true)])
+        .exec()
+        .await
+    {
+        Ok(Some(hackathon)) => Ok(hackathon),
+        Ok(None) => Err(ApiError::NotFound("No active hackathon".to_owned())),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Scopes a list endpoint to one hackathon, so data from past hackathons isn't mixed in.
+#[derive(serde::Deserialize, IntoParams, Validate)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct HackathonScope {
+    /// Defaults to the active hackathon
+    hackathon_id: Option<String>,
+}
+
+impl HackathonScope {
+    pub async fn resolve(self, app_state: &AppState) -> Result<String, ApiError> {
+        match self.hackathon_id {
+            Some(hackathon_id) => Ok(hackathon_id),
+            None => Ok(find_active_hackathon(app_state).await?.id),
+        }
     }
 }
 
@@ -282,13 +508,22 @@ pub fn hackathon_get_router(app_state: AppState) -> Router {
         .route("/", post(create_hackathon).get(get_all_hackathon))
         .route(
             "/:id",
-            get(get_hackathon_by_id).delete(delete_hackathon_by_id),
+            get(get_hackathon_by_id)
+                .patch(update_hackathon_by_id)
+                .delete(delete_hackathon_by_id),
         )
         .route("/:id/active", patch(set_active_hackathon))
+        .route("/:id/scans", get(scans::get_hackathon_scans))
         .route_layer(middleware::from_fn_with_state(
             app_state.clone(),
             auth_guard::require_auth,
         ))
         .route("/active/static", get(get_active_hackathon))
+        .route("/:id/events", get(events::get_hackathon_events))
+        .route("/:id/sponsors", get(sponsors::get_hackathon_sponsors))
+        .route(
+            "/:id/extra_credit/classes",
+            get(extra_credit_classes::get_hackathon_extra_credit_classes),
+        )
         .with_state(app_state)
 }
@@ -1,17 +1,29 @@
 use axum::{
     extract::{Path, State},
     middleware,
-    routing::get,
-    Extension, Json, Router,
+    routing::{get, patch},
+    Json, Router,
 };
 use hyper::StatusCode;
 use serde::Deserialize;
 use utoipa::ToSchema;
+use validator::{Validate, ValidationErrors};
 
 use crate::{
-    auth_guard::{self, permission_check},
-    base_types::{AppState, CreateResponse, DeleteResponse, GetResponse},
-    prisma::{self, location::Data, Role},
+    api_error::ApiError,
+    audit::{self, snapshot},
+    auth_guard::{self, RequirePermission},
+    base_types::{AppState, CreateResponse, DeleteResponse, GetResponse, UpdateResponse},
+    docs::{partial, partial_schema},
+    occupancy::{self, LocationOccupancy},
+    pagination::{Page, PageParams, SortOrder},
+    permissions::require,
+    prisma::{
+        self,
+        location::{self, Data},
+        AuditAction,
+    },
+    validation::{PartialChecks, ValidJson, ValidQuery},
 };
 
 #[derive(Deserialize, ToSchema)]
@@ -19,12 +31,17 @@ use crate::{
 pub struct LocationEntity {
     _id: String,
     _name: String,
+    _capacity: Option<i32>,
 }
 
-#[derive(Deserialize, ToSchema)]
+#[derive(Deserialize, ToSchema, Validate)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateLocationEntity {
+    #[validate(length(min = 1))]
     name: String,
+    /// How many people fit. Leave out for no limit
+    #[validate(range(min = 1))]
+    capacity: Option<i32>,
 }
 
 #[axum::debug_handler]
@@ -34,31 +51,41 @@ pub struct CreateLocationEntity {
     responses(
         (status = 201, description = "Created a new location"),
         (status = 400, description = "Bad request"),
-        (status = 401, description = "Unauthorized")
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 422, description = "Invalid location")
     ),
     request_body = CreateLocationEntity,
     security(
-        ("http" = ["Exec", "Tech", "Team"])
+        ("http" = ["location:write"])
     )
 )]
 async fn create_location(
     State(app_state): State<AppState>,
-    Extension(request_user): Extension<auth_guard::RequestUser>,
-    Json(body): Json<CreateLocationEntity>,
+    RequirePermission(request_user, _): RequirePermission<require::LocationWrite>,
+    ValidJson(body): ValidJson<CreateLocationEntity>,
 ) -> CreateResponse {
-    if !permission_check(request_user, Role::Team, vec![]) {
-        return Err((StatusCode::UNAUTHORIZED, "Unauthorized".to_owned()));
-    }
-
     match app_state
         .client
         .location()
-        .create(body.name, vec![])
+        .create(body.name, vec![location::capacity::set(body.capacity)])
         .exec()
         .await
     {
-        Ok(_location) => Ok((StatusCode::CREATED, ())),
-        Err(err) => Err((StatusCode::BAD_REQUEST, err.to_string())),
+        Ok(location) => {
+            audit::record(
+                &app_state,
+                &request_user,
+                AuditAction::Create,
+                "location",
+                location.id.clone(),
+                None,
+                snapshot(&location),
+            )
+            .await;
+            Ok((StatusCode::CREATED, ()))
+        }
+        Err(err) => Err(err.into()),
     }
 }
 #[axum::debug_handler]
@@ -67,25 +94,46 @@ async fn create_location(
     context_path = "/locations",
     path = "",
     responses(
-        (status = 200, description = "Get all locations", body = [LocationEntity]),
+        (status = 200, description = "Get a page of locations, by name", body = LocationPage),
         (status = 400, description = "Bad request"),
         (status = 401, description = "Unauthorized"),
-        (status = 404, description = "Not found")
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found"),
+        (status = 422, description = "Invalid query parameters")
     ),
+    params(PageParams),
     security(
-        ("http" = ["Exec", "Tech", "Team"])
+        ("http" = ["location:read"])
     )
 )]
 async fn get_all_locations(
     State(app_state): State<AppState>,
-    Extension(request_user): Extension<auth_guard::RequestUser>,
-) -> GetResponse<Json<Vec<Data>>> {
-    if !permission_check(request_user, Role::Team, vec![]) {
-        return Err((StatusCode::UNAUTHORIZED, "Unauthorized".to_owned()));
+    ValidQuery(page): ValidQuery<PageParams>,
+    _: RequirePermission<require::LocationRead>,
+) -> GetResponse<Json<Page<Data>>> {
+    let direction = || page.direction(SortOrder::Asc);
+    let mut query = app_state
+        .client
+        .location()
+        .find_many(vec![])
+        .order_by(location::name::order(direction()))
+        .order_by(location::id::order(direction()))
+        .skip(page.skip())
+        .take(page.take());
+    if let Some(cursor) = page.cursor.clone() {
+        query = query.cursor(location::UniqueWhereParam::IdEquals(cursor));
     }
-    match app_state.client.location().find_many(vec![]).exec().await {
-        Ok(locations) => Ok((StatusCode::OK, Json(locations))),
-        Err(err) => Err((StatusCode::BAD_REQUEST, err.to_string())),
+
+    match app_state
+        .client
+        ._batch((query, app_state.client.location().count(vec![])))
+        .await
+    {
+        Ok((locations, total)) => Ok((
+            StatusCode::OK,
+            Json(page.page(locations, total, |location| location.id.clone())),
+        )),
+        Err(err) => Err(err.into()),
     }
 }
 #[axum::debug_handler]
@@ -96,20 +144,18 @@ async fn get_all_locations(
         (status = 200, description = "Get a location by id", body = LocationEntity),
         (status = 400, description = "Bad request"),
         (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
         (status = 404, description = "Not found")
     ),
     security(
-        ("http" = ["Exec", "Tech", "Team"])
+        ("http" = ["location:read"])
     )
 )]
 async fn get_location_by_id(
     State(app_state): State<AppState>,
     Path(id): Path<String>,
-    Extension(request_user): Extension<auth_guard::RequestUser>,
+    _: RequirePermission<require::LocationRead>,
 ) -> GetResponse<Json<Data>> {
-    if !permission_check(request_user, Role::Team, vec![]) {
-        return Err((StatusCode::UNAUTHORIZED, "Unauthorized".to_owned()));
-    }
     match app_state
         .client
         .location()
@@ -119,11 +165,90 @@ async fn get_location_by_id(
     {
         Ok(location) => match location {
             Some(location) => Ok((StatusCode::OK, Json(location))),
-            None => Err((StatusCode::NOT_FOUND, "Location not found".to_owned())),
+            None => Err(ApiError::NotFound("Location not found".to_owned())),
         },
-        Err(err) => Err((StatusCode::BAD_REQUEST, err.to_string())),
+        Err(err) => Err(err.into()),
+    }
+}
+location::partial_unchecked!(LocationUpdateEntity { name capacity });
+
+impl Validate for LocationUpdateEntity {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        PartialChecks::new()
+            .non_empty("name", self.name.as_ref())
+            .check(
+                "capacity",
+                "range",
+                !matches!(self.capacity, Some(Some(capacity)) if capacity < 1),
+            )
+            .finish()
     }
 }
+
+partial_schema!(LocationUpdateEntity {
+    "name": partial::string(),
+    "capacity": partial::integer(),
+});
+
+#[axum::debug_handler]
+#[utoipa::path(
+    patch,
+    path = "/locations/{id}",
+    responses(
+        (status = 200, description = "Updated the given fields of a location by id"),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found"),
+        (status = 422, description = "Invalid location")
+    ),
+    request_body = LocationUpdateEntity,
+    security(
+        ("http" = ["location:write"])
+    )
+)]
+async fn update_location_by_id(
+    State(app_state): State<AppState>,
+    Path(id): Path<String>,
+    RequirePermission(request_user, _): RequirePermission<require::LocationWrite>,
+    ValidJson(body): ValidJson<LocationUpdateEntity>,
+) -> UpdateResponse {
+    let before = match app_state
+        .client
+        .location()
+        .find_unique(location::UniqueWhereParam::IdEquals(id.clone()))
+        .exec()
+        .await
+    {
+        Ok(Some(location)) => location,
+        Ok(None) => return Err(ApiError::NotFound("Location not found".to_owned())),
+        Err(err) => return Err(err.into()),
+    };
+
+    match app_state
+        .client
+        .location()
+        .update_unchecked(location::UniqueWhereParam::IdEquals(id), body.to_params())
+        .exec()
+        .await
+    {
+        Ok(after) => {
+            audit::record(
+                &app_state,
+                &request_user,
+                AuditAction::Update,
+                "location",
+                after.id.clone(),
+                snapshot(&before),
+                snapshot(&after),
+            )
+            .await;
+            Ok((StatusCode::OK, ()))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
 #[axum::debug_handler]
 #[utoipa::path(
     delete,
@@ -132,20 +257,18 @@ async fn get_location_by_id(
         (status = 200, description = "Delete a location by id"),
         (status = 400, description = "Bad request"),
         (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
         (status = 404, description = "Not found")
     ),
     security(
-        ("http" = ["Exec", "Tech", "Team"])
+        ("http" = ["location:write"])
     )
 )]
 async fn delete_location_by_id(
     State(app_state): State<AppState>,
     Path(id): Path<String>,
-    Extension(request_user): Extension<auth_guard::RequestUser>,
+    RequirePermission(request_user, _): RequirePermission<require::LocationWrite>,
 ) -> DeleteResponse {
-    if !permission_check(request_user, Role::Team, vec![]) {
-        return Err((StatusCode::UNAUTHORIZED, "Unauthorized".to_owned()));
-    }
     match app_state
         .client
         .location()
@@ -153,18 +276,57 @@ async fn delete_location_by_id(
         .exec()
         .await
     {
-        Ok(_) => Ok((StatusCode::NO_CONTENT, ())),
-        Err(err) => Err((StatusCode::BAD_REQUEST, err.to_string())),
+        Ok(location) => {
+            audit::record(
+                &app_state,
+                &request_user,
+                AuditAction::Delete,
+                "location",
+                location.id.clone(),
+                snapshot(&location),
+                None,
+            )
+            .await;
+            Ok((StatusCode::NO_CONTENT, ()))
+        }
+        Err(err) => Err(err.into()),
     }
 }
 
+#[axum::debug_handler]
+#[utoipa::path(
+    get,
+    path = "/locations/{id}/occupancy",
+    responses(
+        (status = 200, description = "How many people are at a location's events happening now", body = LocationOccupancy),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found")
+    ),
+    security(
+        ("http" = ["location:read"])
+    )
+)]
+async fn get_location_occupancy(
+    State(app_state): State<AppState>,
+    Path(id): Path<String>,
+    _: RequirePermission<require::LocationRead>,
+) -> GetResponse<Json<LocationOccupancy>> {
+    let occupancy = occupancy::location_occupancy(&app_state.client, id).await?;
+    Ok((StatusCode::OK, Json(occupancy)))
+}
+
 pub fn location_get_router(app_state: AppState) -> Router {
     Router::new()
         .route("/", get(get_all_locations).post(create_location))
         .route(
-            "/{id}",
-            get(get_location_by_id).delete(delete_location_by_id),
+            "/:id",
+            get(get_location_by_id)
+                .patch(update_location_by_id)
+                .delete(delete_location_by_id),
         )
+        .route("/:id/occupancy", get(get_location_occupancy))
         .route_layer(middleware::from_fn_with_state(
             app_state.clone(),
             auth_guard::require_auth,
@@ -0,0 +1,40 @@
+use axum::{extract::State, middleware, routing::get, Json, Router};
+use hyper::StatusCode;
+
+use crate::{
+    auth_guard::{self, RequirePermission},
+    base_types::{AppState, GetResponse},
+    permissions::require,
+    user_cache::CacheStats,
+};
+
+#[axum::debug_handler]
+#[utoipa::path(
+    get,
+    context_path = "/metrics",
+    path = "/auth-cache",
+    responses(
+        (status = 200, description = "Returns hit and miss counts of the authenticated user cache since startup", body = CacheStats),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("api_key" = ["metrics:read"])
+    )
+)]
+pub async fn get_auth_cache_stats(
+    State(app_state): State<AppState>,
+    _: RequirePermission<require::MetricsRead>,
+) -> GetResponse<Json<CacheStats>> {
+    Ok((StatusCode::OK, Json(app_state.user_cache.stats())))
+}
+
+pub fn metrics_get_router(app_state: AppState) -> Router {
+    Router::new()
+        .route("/auth-cache", get(get_auth_cache_stats))
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            auth_guard::require_auth,
+        ))
+        .with_state(app_state)
+}
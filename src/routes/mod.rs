@@ -1,8 +1,15 @@
+pub mod audit_logs;
 pub mod events;
 pub mod extra_credit_assignments;
 pub mod extra_credit_classes;
 pub mod hackathons;
 pub mod locations;
+pub mod metrics;
+pub mod organizers;
+pub mod projects;
+pub mod registrations;
 pub mod scans;
+pub mod scores;
 pub mod sponsors;
+pub mod teams;
 pub mod users;
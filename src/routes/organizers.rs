@@ -1,100 +1,279 @@
 use axum::{
-    extract::{Path, State},
-    routing::{delete, get, post},
+    extract::{Path, Query, State},
+    middleware,
+    routing::{get, put},
     Json, Router,
 };
 use hyper::StatusCode;
 use serde::Deserialize;
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
+use validator::{Validate, ValidationError};
 
 use crate::{
-    base_types::{AppState, CreateResponse, DeleteResponse, GetResponse},
-    prisma::{
-        organizer::{Data, UniqueWhereParam},
-        Role,
-    },
+    api_error::ApiError,
+    auth_guard::{self, RequestUser, RequirePermission},
+    base_types::{AppState, GetResponse},
+    permissions::{require, Permission, PermissionTable},
+    prisma::{privilege_change, user, Role},
+    socket,
+    validation::ValidJson,
 };
 
 #[derive(Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
-struct CreateOrganizerEntity {
+#[allow(unused)]
+pub struct OrganizerEntity {
+    id: String,
     first_name: String,
     last_name: String,
     email: String,
-    privilege: Role,
     gcp_id: String,
+    #[schema(value_type = String)]
+    privilege: Role,
 }
 
-async fn create_organizer(
-    State(app_state): State<AppState>,
-    Json(body): Json<CreateOrganizerEntity>,
-) -> CreateResponse {
-    match app_state
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[allow(unused)]
+pub struct PrivilegeChangeEntity {
+    id: String,
+    user_id: String,
+    changed_by_id: String,
+    #[schema(value_type = String)]
+    previous_privilege: Role,
+    #[schema(value_type = String)]
+    new_privilege: Role,
+    time: String,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct GrantPrivilegeEntity {
+    /// One of VOLUNTEER, TEAM, EXEC, TECH, FINANCE or SPONSOR, with no permissions you don't hold
+    #[schema(value_type = String)]
+    #[validate(custom = "validate_granted_role")]
+    privilege: Role,
+}
+
+// Taking a privilege away is `DELETE /{id}/privilege`, so it shows up as a revocation
+fn validate_granted_role(role: &Role) -> Result<(), ValidationError> {
+    match role {
+        Role::None => Err(ValidationError::new("use_revoke")),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub struct PrivilegeChangeParams {
+    /// Only changes made to this user
+    user_id: Option<String>,
+}
+
+/// Sets `target_id`'s privilege to `privilege` and records the change, as long as the requesting
+/// user's role holds every permission of both the user's current role and the new one.
+async fn change_privilege(
+    app_state: &AppState,
+    request_user: RequestUser,
+    target_id: String,
+    privilege: Role,
+) -> Result<user::Data, ApiError> {
+    if target_id == request_user.id {
+        return Err(ApiError::Forbidden(
+            "You cannot change your own privilege".to_owned(),
+        ));
+    }
+    let table = PermissionTable::global();
+    if !table.covers(request_user.privilege, privilege) {
+        return Err(ApiError::Forbidden(
+            "You cannot grant a privilege with permissions you don't hold".to_owned(),
+        ));
+    }
+
+    let target = match app_state
         .client
-        .organizer()
-        .create(
-            body.first_name,
-            body.last_name,
-            body.email,
-            body.privilege,
-            body.gcp_id,
-            vec![],
-        )
+        .user()
+        .find_unique(user::UniqueWhereParam::IdEquals(target_id))
         .exec()
         .await
     {
-        Ok(_organizer) => Ok((StatusCode::CREATED, ())),
-        Err(err) => Err((StatusCode::BAD_REQUEST, err.to_string())),
+        Ok(Some(target)) => target,
+        Ok(None) => return Err(ApiError::NotFound("User not found".to_owned())),
+        Err(err) => return Err(err.into()),
+    };
+
+    if !table.covers(request_user.privilege, target.privilege) {
+        return Err(ApiError::Forbidden(
+            "You cannot change the privilege of someone with permissions you don't hold".to_owned(),
+        ));
+    }
+    if target.privilege == privilege {
+        return Ok(target);
     }
-}
 
-async fn get_all_organizers(State(app_state): State<AppState>) -> GetResponse<Json<Vec<Data>>> {
-    match app_state.client.organizer().find_many(vec![]).exec().await {
-        Ok(organizers) => Ok((StatusCode::OK, Json(organizers))),
-        Err(err) => Err((StatusCode::NOT_FOUND, err.to_string())),
+    match app_state
+        .client
+        ._batch((
+            app_state.client.user().update(
+                user::UniqueWhereParam::IdEquals(target.id.clone()),
+                vec![user::privilege::set(privilege)],
+            ),
+            app_state.client.privilege_change().create(
+                target.id,
+                request_user.id,
+                target.privilege,
+                privilege,
+                vec![],
+            ),
+        ))
+        .await
+    {
+        Ok((user, _)) => {
+            app_state.user_cache.invalidate_user(&user.id);
+            socket::disconnect_user(app_state, &user.id);
+            Ok(user)
+        }
+        Err(err) => Err(err.into()),
     }
 }
 
-async fn get_organizer_by_id(
+#[axum::debug_handler]
+#[utoipa::path(
+    get,
+    context_path = "/organizers",
+    path = "",
+    responses(
+        (status = 200, description = "Returns every user with a privilege above NONE", body = [OrganizerEntity]),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("api_key" = ["organizers:manage"])
+    )
+)]
+pub async fn get_all_organizers(
     State(app_state): State<AppState>,
-    Path(id): Path<String>,
-) -> GetResponse<Json<Data>> {
+    _: RequirePermission<require::OrganizersManage>,
+) -> GetResponse<Json<Vec<user::Data>>> {
     match app_state
         .client
-        .organizer()
-        .find_unique(crate::prisma::organizer::UniqueWhereParam::IdEquals(id))
+        .user()
+        // Every organizer role judges, which is what sets them apart from hackers and sponsors
+        .find_many(vec![user::privilege::in_vec(
+            PermissionTable::global().roles_with(Permission::ScoresWrite),
+        )])
         .exec()
         .await
     {
-        Ok(organizer) => match organizer {
-            Some(organizer) => Ok((StatusCode::OK, Json(organizer))),
-            None => Err((StatusCode::NOT_FOUND, "No organizer found".to_owned())),
-        },
-        Err(err) => Err((StatusCode::INTERNAL_SERVER_ERROR, err.to_string())),
+        Ok(organizers) => Ok((StatusCode::OK, Json(organizers))),
+        Err(err) => Err(err.into()),
     }
 }
 
-async fn delete_organizer_by_id(
+#[axum::debug_handler]
+#[utoipa::path(
+    put,
+    context_path = "/organizers",
+    path = "/{id}/privilege",
+    responses(
+        (status = 200, description = "Granted the privilege and returns the updated user", body = OrganizerEntity),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Missing organizers:manage, the privilege or the user has permissions you don't hold, or the user is you"),
+        (status = 404, description = "User not found"),
+        (status = 422, description = "The privilege is NONE, which is revoked with DELETE instead")
+    ),
+    params(("id" = String, Path, description = "id of user being granted the privilege")),
+    request_body = GrantPrivilegeEntity,
+    security(
+        ("api_key" = ["organizers:manage"])
+    )
+)]
+pub async fn grant_privilege(
     State(app_state): State<AppState>,
     Path(id): Path<String>,
-) -> DeleteResponse {
+    RequirePermission(request_user, _): RequirePermission<require::OrganizersManage>,
+    ValidJson(body): ValidJson<GrantPrivilegeEntity>,
+) -> GetResponse<Json<user::Data>> {
+    let user = change_privilege(&app_state, request_user, id, body.privilege).await?;
+    Ok((StatusCode::OK, Json(user)))
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    delete,
+    context_path = "/organizers",
+    path = "/{id}/privilege",
+    responses(
+        (status = 200, description = "Reset the user's privilege to NONE and returns the updated user", body = OrganizerEntity),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Missing organizers:manage, the user has permissions you don't hold, or the user is you"),
+        (status = 404, description = "User not found")
+    ),
+    params(("id" = String, Path, description = "id of user losing their privilege")),
+    security(
+        ("api_key" = ["organizers:manage"])
+    )
+)]
+pub async fn revoke_privilege(
+    State(app_state): State<AppState>,
+    Path(id): Path<String>,
+    RequirePermission(request_user, _): RequirePermission<require::OrganizersManage>,
+) -> GetResponse<Json<user::Data>> {
+    let user = change_privilege(&app_state, request_user, id, Role::None).await?;
+    Ok((StatusCode::OK, Json(user)))
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    get,
+    context_path = "/organizers",
+    path = "/changes",
+    responses(
+        (status = 200, description = "Returns privilege changes, newest first", body = [PrivilegeChangeEntity]),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden")
+    ),
+    params(PrivilegeChangeParams),
+    security(
+        ("api_key" = ["audit:read"])
+    )
+)]
+pub async fn get_privilege_changes(
+    State(app_state): State<AppState>,
+    Query(params): Query<PrivilegeChangeParams>,
+    _: RequirePermission<require::AuditRead>,
+) -> GetResponse<Json<Vec<privilege_change::Data>>> {
+    let mut filters = vec![];
+    if let Some(user_id) = params.user_id {
+        filters.push(privilege_change::user_id::equals(user_id));
+    }
+
     match app_state
         .client
-        .organizer()
-        .delete(UniqueWhereParam::IdEquals(id))
+        .privilege_change()
+        .find_many(filters)
+        .order_by(privilege_change::time::order(
+            prisma_client_rust::Direction::Desc,
+        ))
         .exec()
         .await
     {
-        Ok(_) => Ok((StatusCode::NO_CONTENT, ())),
-        Err(err) => Err((StatusCode::BAD_REQUEST, err.to_string())),
+        Ok(changes) => Ok((StatusCode::OK, Json(changes))),
+        Err(err) => Err(err.into()),
     }
 }
 
-pub fn routes(app_state: AppState) -> Router {
-    axum::Router::new()
-        .route("/", post(create_organizer))
+pub fn organizer_get_router(app_state: AppState) -> Router {
+    Router::new()
         .route("/", get(get_all_organizers))
-        .route("/:id", get(get_organizer_by_id))
-        .route("/:id", delete(delete_organizer_by_id))
+        .route("/changes", get(get_privilege_changes))
+        .route(
+            "/:id/privilege",
+            put(grant_privilege).delete(revoke_privilege),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            auth_guard::require_auth,
+        ))
         .with_state(app_state)
 }
@@ -0,0 +1,625 @@
+use std::collections::HashSet;
+
+use axum::{
+    extract::{Path, State},
+    middleware,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use hyper::StatusCode;
+use prisma_client_rust::prisma_errors::query_engine::UniqueKeyViolation;
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    api_error::ApiError,
+    auth_guard::{self, RequirePermission},
+    base_types::{AppState, CreateResponse, DeleteResponse, GetResponse, UpdateResponse},
+    judge_assignment::{plan_assignments, AssignmentPlan, JudgeAssignment, ProjectToJudge},
+    pagination::{Page, PageParams, SortOrder},
+    permissions::{require, Permission, PermissionTable},
+    prisma::{
+        hackathon,
+        project::{self, Data, UniqueWhereParam},
+        registration, score, user,
+    },
+    routes::hackathons::find_active_hackathon,
+    validation::{invalid_field, ValidJson, ValidQuery},
+};
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[allow(unused)]
+pub struct ProjectEntity {
+    id: String,
+    name: String,
+    hackathon_id: String,
+    join_code: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateProjectEntity {
+    #[validate(length(min = 1, max = 100))]
+    name: String,
+    /// Defaults to the active hackathon
+    hackathon_id: Option<Uuid>,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateProjectEntity {
+    #[validate(length(min = 1, max = 100))]
+    name: String,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct AssignJudgeEntity {
+    judge_id: Uuid,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoAssignJudgesEntity {
+    #[validate(range(min = 1))]
+    judges_per_project: u32,
+    /// From 1 to 1000
+    #[validate(range(min = 1, max = 1000))]
+    max_projects_per_judge: u32,
+    /// Defaults to the active hackathon
+    hackathon_id: Option<Uuid>,
+    /// Defaults to every user allowed to judge
+    judge_ids: Option<Vec<Uuid>>,
+}
+
+#[derive(Deserialize, IntoParams, Validate)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct ProjectParams {
+    /// Defaults to the active hackathon
+    hackathon_id: Option<String>,
+}
+
+pub async fn find_project(app_state: &AppState, id: String) -> Result<Data, ApiError> {
+    match app_state
+        .client
+        .project()
+        .find_unique(UniqueWhereParam::IdEquals(id))
+        .exec()
+        .await
+    {
+        Ok(Some(project)) => Ok(project),
+        Ok(None) => Err(ApiError::NotFound("Project not found".to_owned())),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    post,
+    context_path = "/projects",
+    path = "",
+    responses(
+        (status = 201, description = "Created a new project"),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 422, description = "Invalid project")
+    ),
+    request_body = CreateProjectEntity,
+    security(
+        ("api_key" = ["project:write"])
+    )
+)]
+pub async fn create_project(
+    State(app_state): State<AppState>,
+    _: RequirePermission<require::ProjectWrite>,
+    ValidJson(body): ValidJson<CreateProjectEntity>,
+) -> CreateResponse {
+    let hackathon_id = match body.hackathon_id {
+        Some(hackathon_id) => {
+            app_state
+                .client
+                .hackathon()
+                .find_unique(hackathon::UniqueWhereParam::IdEquals(
+                    hackathon_id.to_string(),
+                ))
+                .exec()
+                .await?
+                .ok_or_else(|| {
+                    invalid_field("hackathonId", "not_found", "No hackathon with this id")
+                })?
+                .id
+        }
+        None => find_active_hackathon(&app_state).await?.id,
+    };
+
+    match app_state
+        .client
+        .project()
+        .create(
+            body.name,
+            hackathon::UniqueWhereParam::IdEquals(hackathon_id),
+            vec![],
+        )
+        .exec()
+        .await
+    {
+        Ok(_project) => Ok((StatusCode::CREATED, ())),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    get,
+    context_path = "/projects",
+    path = "",
+    responses(
+        (status = 200, description = "Returns a page of projects for the hackathon, by name", body = ProjectPage),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "No active hackathon"),
+        (status = 422, description = "Invalid query parameters")
+    ),
+    params(PageParams, ProjectParams),
+    security(
+        ("api_key" = ["project:read"])
+    )
+)]
+pub async fn get_all_projects(
+    State(app_state): State<AppState>,
+    ValidQuery(page): ValidQuery<PageParams>,
+    ValidQuery(params): ValidQuery<ProjectParams>,
+    _: RequirePermission<require::ProjectRead>,
+) -> GetResponse<Json<Page<Data>>> {
+    let hackathon_id = match params.hackathon_id {
+        Some(hackathon_id) => hackathon_id,
+        None => find_active_hackathon(&app_state).await?.id,
+    };
+    let filters = vec![project::hackathon_id::equals(hackathon_id)];
+
+    let direction = || page.direction(SortOrder::Asc);
+    let mut query = app_state
+        .client
+        .project()
+        .find_many(filters.clone())
+        .order_by(project::name::order(direction()))
+        .order_by(project::id::order(direction()))
+        .skip(page.skip())
+        .take(page.take());
+    if let Some(cursor) = page.cursor.clone() {
+        query = query.cursor(UniqueWhereParam::IdEquals(cursor));
+    }
+
+    match app_state
+        .client
+        ._batch((query, app_state.client.project().count(filters)))
+        .await
+    {
+        Ok((projects, total)) => Ok((
+            StatusCode::OK,
+            Json(page.page(projects, total, |project| project.id.clone())),
+        )),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    get,
+    context_path = "/projects",
+    path = "/{id}",
+    responses(
+        (status = 200, description = "Returns project with id", body = ProjectEntity),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Project not found")
+    ),
+    params(("id" = String, Path, description = "id of project to get")),
+    security(
+        ("api_key" = ["project:read"])
+    )
+)]
+pub async fn get_project_by_id(
+    State(app_state): State<AppState>,
+    Path(id): Path<String>,
+    _: RequirePermission<require::ProjectRead>,
+) -> GetResponse<Json<Data>> {
+    let project = find_project(&app_state, id).await?;
+    Ok((StatusCode::OK, Json(project)))
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    patch,
+    context_path = "/projects",
+    path = "/{id}",
+    responses(
+        (status = 200, description = "Renamed project with id"),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 422, description = "Invalid project")
+    ),
+    params(("id" = String, Path, description = "id of project to update")),
+    request_body = UpdateProjectEntity,
+    security(
+        ("api_key" = ["project:write"])
+    )
+)]
+pub async fn update_project_by_id(
+    State(app_state): State<AppState>,
+    Path(id): Path<String>,
+    _: RequirePermission<require::ProjectWrite>,
+    ValidJson(body): ValidJson<UpdateProjectEntity>,
+) -> UpdateResponse {
+    match app_state
+        .client
+        .project()
+        .update(
+            UniqueWhereParam::IdEquals(id),
+            vec![project::name::set(body.name)],
+        )
+        .exec()
+        .await
+    {
+        Ok(_) => Ok((StatusCode::OK, ())),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    delete,
+    context_path = "/projects",
+    path = "/{id}",
+    responses(
+        (status = 204, description = "Deleted project with id along with its scores"),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden")
+    ),
+    params(("id" = String, Path, description = "id of project to delete")),
+    security(
+        ("api_key" = ["project:delete"])
+    )
+)]
+pub async fn delete_project_by_id(
+    State(app_state): State<AppState>,
+    Path(id): Path<String>,
+    _: RequirePermission<require::ProjectDelete>,
+) -> DeleteResponse {
+    // Detach team members and drop scores first so the foreign keys don't block the delete
+    match app_state
+        .client
+        ._batch((
+            app_state.client.registration().update_many(
+                vec![registration::project_id::equals(Some(id.clone()))],
+                vec![registration::project_id::set(None)],
+            ),
+            app_state
+                .client
+                .score()
+                .delete_many(vec![score::project_id::equals(id.clone())]),
+            app_state
+                .client
+                .project()
+                .delete(UniqueWhereParam::IdEquals(id)),
+        ))
+        .await
+    {
+        Ok(_) => Ok((StatusCode::NO_CONTENT, ())),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    post,
+    context_path = "/projects",
+    path = "/{id}/judges",
+    responses(
+        (status = 201, description = "Assigned judge to project"),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Project or judge not found"),
+        (status = 409, description = "Judge is already assigned to project or is on its team")
+    ),
+    params(("id" = String, Path, description = "id of project to assign the judge to")),
+    request_body = AssignJudgeEntity,
+    security(
+        ("api_key" = ["judges:assign"])
+    )
+)]
+pub async fn assign_judge_to_project(
+    State(app_state): State<AppState>,
+    Path(id): Path<String>,
+    _: RequirePermission<require::JudgesAssign>,
+    ValidJson(body): ValidJson<AssignJudgeEntity>,
+) -> CreateResponse {
+    let project = find_project(&app_state, id).await?;
+
+    let judge = match app_state
+        .client
+        .user()
+        .find_unique(user::UniqueWhereParam::IdEquals(body.judge_id.to_string()))
+        .exec()
+        .await
+    {
+        Ok(Some(judge)) => judge,
+        Ok(None) => return Err(ApiError::NotFound("Judge not found".to_owned())),
+        Err(err) => return Err(err.into()),
+    };
+
+    if !PermissionTable::global().allows(judge.privilege, Permission::ScoresWrite) {
+        return Err(ApiError::BadRequest(
+            "User is not allowed to judge".to_owned(),
+        ));
+    }
+
+    let members = app_state
+        .client
+        .registration()
+        .find_many(vec![registration::project_id::equals(Some(
+            project.id.clone(),
+        ))])
+        .exec()
+        .await?;
+    let to_judge = ProjectToJudge {
+        project_id: project.id.clone(),
+        member_ids: members
+            .into_iter()
+            .map(|registration| registration.user_id)
+            .collect(),
+    };
+    if !to_judge.can_be_judged_by(&judge.id) {
+        return Err(ApiError::Conflict(
+            "Judge is on the project's team".to_owned(),
+        ));
+    }
+
+    // An unsubmitted score row is how a judge assignment is represented
+    match app_state
+        .client
+        .score()
+        .create(
+            project.hackathon_id,
+            UniqueWhereParam::IdEquals(project.id),
+            user::UniqueWhereParam::IdEquals(judge.id),
+            false,
+            0,
+            0,
+            0,
+            0,
+            0,
+            vec![],
+        )
+        .exec()
+        .await
+    {
+        Ok(_score) => Ok((StatusCode::CREATED, ())),
+        Err(err) if err.is_prisma_error::<UniqueKeyViolation>() => Err(ApiError::Conflict(
+            "Judge is already assigned to project".to_owned(),
+        )),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    delete,
+    context_path = "/projects",
+    path = "/{id}/judges/{judge_id}",
+    responses(
+        (status = 204, description = "Unassigned judge from project"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Judge is not assigned to project"),
+        (status = 409, description = "Judge has already submitted a score")
+    ),
+    params(
+        ("id" = String, Path, description = "id of project"),
+        ("judge_id" = String, Path, description = "id of judge to unassign")
+    ),
+    security(
+        ("api_key" = ["judges:assign"])
+    )
+)]
+pub async fn unassign_judge_from_project(
+    State(app_state): State<AppState>,
+    Path((id, judge_id)): Path<(String, String)>,
+    _: RequirePermission<require::JudgesAssign>,
+) -> DeleteResponse {
+    let score = match app_state
+        .client
+        .score()
+        .find_unique(score::UniqueWhereParam::ProjectIdJudgeIdEquals(
+            id.clone(),
+            judge_id.clone(),
+        ))
+        .exec()
+        .await
+    {
+        Ok(Some(score)) => score,
+        Ok(None) => {
+            return Err(ApiError::NotFound(
+                "Judge is not assigned to project".to_owned(),
+            ))
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    if score.submitted {
+        return Err(ApiError::Conflict(
+            "Judge has already submitted a score".to_owned(),
+        ));
+    }
+
+    match app_state
+        .client
+        .score()
+        .delete(score::UniqueWhereParam::ProjectIdJudgeIdEquals(
+            id, judge_id,
+        ))
+        .exec()
+        .await
+    {
+        Ok(_) => Ok((StatusCode::NO_CONTENT, ())),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    post,
+    context_path = "/projects",
+    path = "/assign-judges",
+    responses(
+        (status = 201, description = "Assigned judges to projects and returns the plan", body = AssignmentPlan),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "No active hackathon"),
+        (status = 422, description = "Invalid assignment limits")
+    ),
+    request_body = AutoAssignJudgesEntity,
+    security(
+        ("api_key" = ["judges:auto_assign"])
+    )
+)]
+pub async fn auto_assign_judges(
+    State(app_state): State<AppState>,
+    _: RequirePermission<require::JudgesAutoAssign>,
+    ValidJson(body): ValidJson<AutoAssignJudgesEntity>,
+) -> GetResponse<Json<AssignmentPlan>> {
+    let hackathon_id = match body.hackathon_id {
+        Some(hackathon_id) => hackathon_id.to_string(),
+        None => find_active_hackathon(&app_state).await?.id,
+    };
+
+    let projects = match app_state
+        .client
+        .project()
+        .find_many(vec![project::hackathon_id::equals(hackathon_id.clone())])
+        .with(project::registration::fetch(vec![]))
+        .exec()
+        .await
+    {
+        Ok(projects) => projects,
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut judge_filters = vec![user::privilege::in_vec(
+        PermissionTable::global().roles_with(Permission::ScoresWrite),
+    )];
+    if let Some(judge_ids) = body.judge_ids {
+        judge_filters.push(user::id::in_vec(
+            judge_ids.iter().map(Uuid::to_string).collect(),
+        ));
+    }
+    let judges = match app_state
+        .client
+        .user()
+        .find_many(judge_filters)
+        .exec()
+        .await
+    {
+        Ok(judges) => judges,
+        Err(err) => return Err(err.into()),
+    };
+
+    let existing = match app_state
+        .client
+        .score()
+        .find_many(vec![score::hackathon_id::equals(hackathon_id.clone())])
+        .exec()
+        .await
+    {
+        Ok(scores) => scores,
+        Err(err) => return Err(err.into()),
+    };
+
+    let projects: Vec<ProjectToJudge> = projects
+        .into_iter()
+        .map(|project| ProjectToJudge {
+            member_ids: project
+                .registration
+                .unwrap_or_default()
+                .into_iter()
+                .map(|registration| registration.user_id)
+                .collect::<HashSet<String>>(),
+            project_id: project.id,
+        })
+        .collect();
+    let judge_ids: Vec<String> = judges.into_iter().map(|judge| judge.id).collect();
+    let existing: Vec<JudgeAssignment> = existing
+        .into_iter()
+        .map(|score| JudgeAssignment {
+            project_id: score.project_id,
+            judge_id: score.judge_id,
+        })
+        .collect();
+
+    let plan = plan_assignments(
+        &projects,
+        &judge_ids,
+        &existing,
+        body.judges_per_project as usize,
+        body.max_projects_per_judge as usize,
+    );
+
+    // Unsubmitted score rows are how judge assignments are represented
+    match app_state
+        .client
+        .score()
+        .create_many(
+            plan.assignments
+                .iter()
+                .map(|assignment| {
+                    score::create_unchecked(
+                        hackathon_id.clone(),
+                        assignment.project_id.clone(),
+                        assignment.judge_id.clone(),
+                        false,
+                        0,
+                        0,
+                        0,
+                        0,
+                        0,
+                        vec![],
+                    )
+                })
+                .collect(),
+        )
+        .skip_duplicates()
+        .exec()
+        .await
+    {
+        Ok(_) => Ok((StatusCode::CREATED, Json(plan))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub fn project_get_router(app_state: AppState) -> Router {
+    Router::new()
+        .route("/", get(get_all_projects).post(create_project))
+        .route("/assign-judges", post(auto_assign_judges))
+        .route(
+            "/:id",
+            get(get_project_by_id)
+                .patch(update_project_by_id)
+                .delete(delete_project_by_id),
+        )
+        .route("/:id/judges", post(assign_judge_to_project))
+        .route("/:id/judges/:judge_id", delete(unassign_judge_from_project))
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            auth_guard::require_auth,
+        ))
+        .with_state(app_state)
+}
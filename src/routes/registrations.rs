@@ -0,0 +1,771 @@
+use axum::{
+    body::Bytes,
+    extract::{DefaultBodyLimit, Path, State},
+    http::header,
+    middleware,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Extension, Json, Router,
+};
+use axum_typed_multipart::{FieldData, TryFromMultipart, TypedMultipart};
+use hyper::StatusCode;
+use prisma_client_rust::prisma_errors::query_engine::{ForeignKeyViolation, UniqueKeyViolation};
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+use crate::{
+    api_error::ApiError,
+    auth_guard::{self, forbidden, RequestUser, RequirePermission},
+    base_types::{AppState, CreateResponse, DeleteResponse, GetResponse, UpdateResponse},
+    pagination::{Page, PageParams, SortOrder},
+    permissions::{require, Permission, PermissionTable},
+    prisma::{
+        extra_credit_assignment, hackathon,
+        registration::{self, Data, UniqueWhereParam},
+        scan, user, Role,
+    },
+    routes::hackathons::find_active_hackathon,
+    storage_bucket_service::{stream_file, upload_file, Buckets},
+    utils::PHONE_VALIDATOR,
+    validation::{must_be_true, nullable, ValidJson, ValidQuery},
+};
+
+const MAX_RESUME_SIZE: usize = 5 * 1024 * 1024;
+const PDF_CONTENT_TYPE: &str = "application/pdf";
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[allow(unused)]
+pub struct RegistrationEntity {
+    id: String,
+    user_id: String,
+    hackathon_id: String,
+    gender: String,
+    phone: String,
+    country: String,
+    race: Option<String>,
+    travel_reimbursement: bool,
+    driving: bool,
+    first_hackathon: bool,
+    academic_year: String,
+    educational_institution_type: String,
+    coding_experience: Option<String>,
+    shirt_size: String,
+    dietary_restrictions: Option<String>,
+    allergies: Option<String>,
+    eighteen_before_event: bool,
+    mlh_coc: bool,
+    mlh_dcp: bool,
+    reference: Option<String>,
+    resume: Option<String>,
+    university: String,
+    major: String,
+    project_id: Option<String>,
+    expectations: Option<String>,
+    share_address_mlh: Option<bool>,
+    share_address_sponsors: Option<bool>,
+    share_email_mlh: Option<bool>,
+    veteran: bool,
+    time: chrono::DateTime<chrono::FixedOffset>,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateRegistrationEntity {
+    #[validate(length(min = 1))]
+    gender: String,
+    #[validate(regex = "PHONE_VALIDATOR")]
+    phone: String,
+    #[validate(length(min = 1))]
+    country: String,
+    race: Option<String>,
+    travel_reimbursement: bool,
+    driving: bool,
+    first_hackathon: bool,
+    #[validate(length(min = 1))]
+    academic_year: String,
+    #[validate(length(min = 1))]
+    educational_institution_type: String,
+    coding_experience: Option<String>,
+    #[validate(length(min = 1))]
+    shirt_size: String,
+    dietary_restrictions: Option<String>,
+    allergies: Option<String>,
+    eighteen_before_event: bool,
+    #[validate(custom = "must_be_true")]
+    mlh_coc: bool,
+    #[validate(custom = "must_be_true")]
+    mlh_dcp: bool,
+    reference: Option<String>,
+    #[validate(length(min = 1))]
+    university: String,
+    #[validate(length(min = 1))]
+    major: String,
+    expectations: Option<String>,
+    share_address_mlh: Option<bool>,
+    share_address_sponsors: Option<bool>,
+    share_email_mlh: Option<bool>,
+    veteran: bool,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateRegistrationEntity {
+    #[validate(length(min = 1))]
+    gender: Option<String>,
+    #[validate(regex = "PHONE_VALIDATOR")]
+    phone: Option<String>,
+    #[validate(length(min = 1))]
+    country: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<String>)]
+    race: Option<Option<String>>,
+    travel_reimbursement: Option<bool>,
+    driving: Option<bool>,
+    #[validate(length(min = 1))]
+    academic_year: Option<String>,
+    #[validate(length(min = 1))]
+    educational_institution_type: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<String>)]
+    coding_experience: Option<Option<String>>,
+    #[validate(length(min = 1))]
+    shirt_size: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<String>)]
+    dietary_restrictions: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<String>)]
+    allergies: Option<Option<String>>,
+    #[validate(length(min = 1))]
+    university: Option<String>,
+    #[validate(length(min = 1))]
+    major: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<String>)]
+    expectations: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<bool>)]
+    share_address_mlh: Option<Option<bool>>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<bool>)]
+    share_address_sponsors: Option<Option<bool>>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<bool>)]
+    share_email_mlh: Option<Option<bool>>,
+}
+
+impl UpdateRegistrationEntity {
+    fn into_params(self) -> Vec<registration::SetParam> {
+        let mut params = vec![];
+        if let Some(gender) = self.gender {
+            params.push(registration::gender::set(gender));
+        }
+        if let Some(phone) = self.phone {
+            params.push(registration::phone::set(phone));
+        }
+        if let Some(country) = self.country {
+            params.push(registration::country::set(country));
+        }
+        if let Some(race) = self.race {
+            params.push(registration::race::set(race));
+        }
+        if let Some(travel_reimbursement) = self.travel_reimbursement {
+            params.push(registration::travel_reimbursement::set(
+                travel_reimbursement,
+            ));
+        }
+        if let Some(driving) = self.driving {
+            params.push(registration::driving::set(driving));
+        }
+        if let Some(academic_year) = self.academic_year {
+            params.push(registration::academic_year::set(academic_year));
+        }
+        if let Some(educational_institution_type) = self.educational_institution_type {
+            params.push(registration::educational_institution_type::set(
+                educational_institution_type,
+            ));
+        }
+        if let Some(coding_experience) = self.coding_experience {
+            params.push(registration::coding_experience::set(coding_experience));
+        }
+        if let Some(shirt_size) = self.shirt_size {
+            params.push(registration::shirt_size::set(shirt_size));
+        }
+        if let Some(dietary_restrictions) = self.dietary_restrictions {
+            params.push(registration::dietary_restrictions::set(
+                dietary_restrictions,
+            ));
+        }
+        if let Some(allergies) = self.allergies {
+            params.push(registration::allergies::set(allergies));
+        }
+        if let Some(university) = self.university {
+            params.push(registration::university::set(university));
+        }
+        if let Some(major) = self.major {
+            params.push(registration::major::set(major));
+        }
+        if let Some(expectations) = self.expectations {
+            params.push(registration::expectations::set(expectations));
+        }
+        if let Some(share_address_mlh) = self.share_address_mlh {
+            params.push(registration::share_address_mlh::set(share_address_mlh));
+        }
+        if let Some(share_address_sponsors) = self.share_address_sponsors {
+            params.push(registration::share_address_sponsors::set(
+                share_address_sponsors,
+            ));
+        }
+        if let Some(share_email_mlh) = self.share_email_mlh {
+            params.push(registration::share_email_mlh::set(share_email_mlh));
+        }
+        params
+    }
+}
+
+#[derive(Deserialize, IntoParams, Validate)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct RegistrationFilterParams {
+    /// Defaults to the active hackathon
+    hackathon_id: Option<String>,
+    university: Option<String>,
+    academic_year: Option<String>,
+    shirt_size: Option<String>,
+    travel_reimbursement: Option<bool>,
+    first_hackathon: Option<bool>,
+    /// Only registrations made at or after this time
+    from: Option<chrono::DateTime<chrono::FixedOffset>>,
+    /// Only registrations made before this time
+    to: Option<chrono::DateTime<chrono::FixedOffset>>,
+}
+
+impl RegistrationFilterParams {
+    /// Every registration filter, always scoped to `hackathon_id` so a listing never mixes
+    /// hackathons. The handler resolves it from `hackathonId`, or the active hackathon without one.
+    pub fn into_filters(self, hackathon_id: String) -> Vec<registration::WhereParam> {
+        let mut filters = vec![registration::hackathon_id::equals(hackathon_id)];
+        if let Some(university) = self.university {
+            filters.push(registration::university::equals(university));
+        }
+        if let Some(academic_year) = self.academic_year {
+            filters.push(registration::academic_year::equals(academic_year));
+        }
+        if let Some(shirt_size) = self.shirt_size {
+            filters.push(registration::shirt_size::equals(shirt_size));
+        }
+        if let Some(travel_reimbursement) = self.travel_reimbursement {
+            filters.push(registration::travel_reimbursement::equals(
+                travel_reimbursement,
+            ));
+        }
+        if let Some(first_hackathon) = self.first_hackathon {
+            filters.push(registration::first_hackathon::equals(first_hackathon));
+        }
+        if let Some(from) = self.from {
+            filters.push(registration::time::gte(from));
+        }
+        if let Some(to) = self.to {
+            filters.push(registration::time::lt(to));
+        }
+        filters
+    }
+}
+
+async fn find_registration(app_state: &AppState, id: String) -> Result<Data, ApiError> {
+    match app_state
+        .client
+        .registration()
+        .find_unique(UniqueWhereParam::IdEquals(id))
+        .exec()
+        .await
+    {
+        Ok(Some(registration)) => Ok(registration),
+        Ok(None) => Err(ApiError::NotFound("Registration not found".to_owned())),
+        Err(err) => Err(err.into()),
+    }
+}
+
+async fn find_my_registration(app_state: &AppState, user_id: String) -> Result<Data, ApiError> {
+    let hackathon = find_active_hackathon(app_state).await?;
+
+    match app_state
+        .client
+        .registration()
+        .find_unique(UniqueWhereParam::UserIdHackathonIdEquals(
+            user_id,
+            hackathon.id,
+        ))
+        .exec()
+        .await
+    {
+        Ok(Some(registration)) => Ok(registration),
+        Ok(None) => Err(ApiError::NotFound("Registration not found".to_owned())),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Whether a user may act on a registration: its owner always can, anyone else only while their
+/// role holds `permission`.
+pub fn can_access(
+    table: &PermissionTable,
+    owner_id: &str,
+    user_id: &str,
+    role: Role,
+    permission: Permission,
+) -> bool {
+    owner_id == user_id || table.allows(role, permission)
+}
+
+fn owner_check(
+    registration: &Data,
+    request_user: &RequestUser,
+    permission: Permission,
+) -> Result<(), ApiError> {
+    if can_access(
+        PermissionTable::global(),
+        &registration.user_id,
+        &request_user.id,
+        request_user.privilege,
+        permission,
+    ) {
+        Ok(())
+    } else {
+        Err(forbidden())
+    }
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    post,
+    context_path = "/registrations",
+    path = "",
+    responses(
+        (status = 201, description = "Registered the current user for the active hackathon"),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "No active hackathon"),
+        (status = 409, description = "User is already registered for the active hackathon"),
+        (status = 422, description = "Invalid registration")
+    ),
+    request_body = CreateRegistrationEntity,
+    security(
+        ("api_key" = [])
+    )
+)]
+pub async fn create_registration(
+    State(app_state): State<AppState>,
+    Extension(request_user): Extension<RequestUser>,
+    ValidJson(body): ValidJson<CreateRegistrationEntity>,
+) -> CreateResponse {
+    let hackathon = find_active_hackathon(&app_state).await?;
+
+    match app_state
+        .client
+        .registration()
+        .create(
+            user::UniqueWhereParam::IdEquals(request_user.id),
+            body.gender,
+            body.phone,
+            body.country,
+            body.travel_reimbursement,
+            body.driving,
+            body.first_hackathon,
+            body.academic_year,
+            body.educational_institution_type,
+            body.shirt_size,
+            body.eighteen_before_event,
+            body.mlh_coc,
+            body.mlh_dcp,
+            body.university,
+            body.major,
+            body.veteran,
+            hackathon::UniqueWhereParam::IdEquals(hackathon.id),
+            chrono::Utc::now().fixed_offset(),
+            vec![
+                registration::race::set(body.race),
+                registration::coding_experience::set(body.coding_experience),
+                registration::dietary_restrictions::set(body.dietary_restrictions),
+                registration::allergies::set(body.allergies),
+                registration::reference::set(body.reference),
+                registration::expectations::set(body.expectations),
+                registration::share_address_mlh::set(body.share_address_mlh),
+                registration::share_address_sponsors::set(body.share_address_sponsors),
+                registration::share_email_mlh::set(body.share_email_mlh),
+            ],
+        )
+        .exec()
+        .await
+    {
+        Ok(_registration) => Ok((StatusCode::CREATED, ())),
+        Err(err) if err.is_prisma_error::<UniqueKeyViolation>() => Err(ApiError::Conflict(
+            "Already registered for the active hackathon".to_owned(),
+        )),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    get,
+    context_path = "/registrations",
+    path = "",
+    responses(
+        (status = 200, description = "Returns a page of registrations matching the filters, oldest first", body = RegistrationPage),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "No active hackathon"),
+        (status = 422, description = "Invalid query parameters")
+    ),
+    params(PageParams, RegistrationFilterParams),
+    security(
+        ("api_key" = ["registration:read"])
+    )
+)]
+pub async fn get_all_registrations(
+    State(app_state): State<AppState>,
+    ValidQuery(page): ValidQuery<PageParams>,
+    ValidQuery(mut params): ValidQuery<RegistrationFilterParams>,
+    _: RequirePermission<require::RegistrationRead>,
+) -> GetResponse<Json<Page<Data>>> {
+    let hackathon_id = match params.hackathon_id.take() {
+        Some(hackathon_id) => hackathon_id,
+        None => find_active_hackathon(&app_state).await?.id,
+    };
+
+    let filters = params.into_filters(hackathon_id);
+
+    let direction = || page.direction(SortOrder::Asc);
+    let mut query = app_state
+        .client
+        .registration()
+        .find_many(filters.clone())
+        .order_by(registration::time::order(direction()))
+        .order_by(registration::id::order(direction()))
+        .skip(page.skip())
+        .take(page.take());
+    if let Some(cursor) = page.cursor.clone() {
+        query = query.cursor(UniqueWhereParam::IdEquals(cursor));
+    }
+
+    match app_state
+        .client
+        ._batch((query, app_state.client.registration().count(filters)))
+        .await
+    {
+        Ok((registrations, total)) => Ok((
+            StatusCode::OK,
+            Json(page.page(registrations, total, |registration| registration.id.clone())),
+        )),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    get,
+    context_path = "/registrations",
+    path = "/me",
+    responses(
+        (status = 200, description = "Returns the current user's registration for the active hackathon", body = RegistrationEntity),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Not registered for the active hackathon")
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+pub async fn get_my_registration(
+    State(app_state): State<AppState>,
+    Extension(request_user): Extension<RequestUser>,
+) -> GetResponse<Json<Data>> {
+    let registration = find_my_registration(&app_state, request_user.id).await?;
+    Ok((StatusCode::OK, Json(registration)))
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    get,
+    context_path = "/registrations",
+    path = "/{id}",
+    responses(
+        (status = 200, description = "Returns registration with id", body = RegistrationEntity),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not the owner of the registration"),
+        (status = 404, description = "Registration not found")
+    ),
+    params(("id" = String, Path, description = "id of registration to get")),
+    security(
+        ("api_key" = [])
+    )
+)]
+pub async fn get_registration_by_id(
+    State(app_state): State<AppState>,
+    Path(id): Path<String>,
+    Extension(request_user): Extension<RequestUser>,
+) -> GetResponse<Json<Data>> {
+    let registration = find_registration(&app_state, id).await?;
+
+    owner_check(&registration, &request_user, Permission::RegistrationRead)?;
+
+    Ok((StatusCode::OK, Json(registration)))
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    patch,
+    context_path = "/registrations",
+    path = "/{id}",
+    responses(
+        (status = 200, description = "Updated registration with id"),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not the owner of the registration"),
+        (status = 404, description = "Registration not found"),
+        (status = 422, description = "Invalid registration")
+    ),
+    params(("id" = String, Path, description = "id of registration to update")),
+    request_body = UpdateRegistrationEntity,
+    security(
+        ("api_key" = [])
+    )
+)]
+pub async fn update_registration_by_id(
+    State(app_state): State<AppState>,
+    Path(id): Path<String>,
+    Extension(request_user): Extension<RequestUser>,
+    ValidJson(body): ValidJson<UpdateRegistrationEntity>,
+) -> UpdateResponse {
+    let registration = find_registration(&app_state, id).await?;
+
+    owner_check(&registration, &request_user, Permission::RegistrationWrite)?;
+
+    match app_state
+        .client
+        .registration()
+        .update(
+            UniqueWhereParam::IdEquals(registration.id),
+            body.into_params(),
+        )
+        .exec()
+        .await
+    {
+        Ok(_) => Ok((StatusCode::OK, ())),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Scans are the record of who was at which event, so a registration that has been scanned can't
+/// be withdrawn.
+pub fn ensure_withdrawable(scan_count: i64) -> Result<(), ApiError> {
+    if scan_count > 0 {
+        Err(scanned_conflict())
+    } else {
+        Ok(())
+    }
+}
+
+fn scanned_conflict() -> ApiError {
+    ApiError::Conflict(
+        "A registration that has been scanned at an event can't be withdrawn".to_owned(),
+    )
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    delete,
+    context_path = "/registrations",
+    path = "/{id}",
+    responses(
+        (status = 204, description = "Withdrew registration with id"),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not the owner of the registration"),
+        (status = 404, description = "Registration not found"),
+        (status = 409, description = "Registration has been scanned at an event")
+    ),
+    params(("id" = String, Path, description = "id of registration to withdraw")),
+    security(
+        ("api_key" = [])
+    )
+)]
+pub async fn withdraw_registration_by_id(
+    State(app_state): State<AppState>,
+    Path(id): Path<String>,
+    Extension(request_user): Extension<RequestUser>,
+) -> DeleteResponse {
+    let registration = find_registration(&app_state, id).await?;
+
+    owner_check(&registration, &request_user, Permission::RegistrationWrite)?;
+
+    let scan_count = app_state
+        .client
+        .scan()
+        .count(vec![scan::registration_id::equals(registration.id.clone())])
+        .exec()
+        .await?;
+    ensure_withdrawable(scan_count)?;
+
+    // Extra credit sign-ups go with the registration
+    match app_state
+        .client
+        ._batch((
+            app_state.client.extra_credit_assignment().delete_many(vec![
+                extra_credit_assignment::registration_id::equals(registration.id.clone()),
+            ]),
+            app_state
+                .client
+                .registration()
+                .delete(UniqueWhereParam::IdEquals(registration.id)),
+        ))
+        .await
+    {
+        Ok(_) => Ok((StatusCode::NO_CONTENT, ())),
+        // Scanned in after the check above
+        Err(err) if err.is_prisma_error::<ForeignKeyViolation>() => Err(scanned_conflict()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(TryFromMultipart, ToSchema)]
+pub struct UploadResumeEntity {
+    #[form_data(limit = "5MiB")]
+    #[schema(value_type = String, format = Binary)]
+    resume: FieldData<Bytes>,
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    post,
+    context_path = "/registrations",
+    path = "/me/resume",
+    responses(
+        (status = 201, description = "Uploaded resume for the current user's registration"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Not registered for the active hackathon"),
+        (status = 413, description = "Resume is larger than 5MiB"),
+        (status = 415, description = "Resume is not a PDF")
+    ),
+    request_body(content = UploadResumeEntity, content_type = "multipart/form-data"),
+    security(
+        ("api_key" = [])
+    )
+)]
+pub async fn upload_my_resume(
+    State(app_state): State<AppState>,
+    Extension(request_user): Extension<RequestUser>,
+    TypedMultipart(UploadResumeEntity { resume }): TypedMultipart<UploadResumeEntity>,
+) -> CreateResponse {
+    if resume.metadata.content_type.as_deref() != Some(PDF_CONTENT_TYPE)
+        || !resume.contents.starts_with(b"%PDF-")
+    {
+        return Err(ApiError::UnsupportedMediaType(
+            "Resume must be a PDF".to_owned(),
+        ));
+    }
+    if resume.contents.len() > MAX_RESUME_SIZE {
+        return Err(ApiError::PayloadTooLarge(
+            "Resume must be smaller than 5MiB".to_owned(),
+        ));
+    }
+
+    let registration = find_my_registration(&app_state, request_user.id).await?;
+    let file_name = format!("{}.pdf", registration.id);
+
+    upload_file(
+        resume.contents.to_vec(),
+        Buckets::RESUME,
+        Some(&registration.hackathon_id),
+        &file_name,
+        app_state.clone(),
+    )
+    .await?;
+
+    match app_state
+        .client
+        .registration()
+        .update(
+            UniqueWhereParam::IdEquals(registration.id),
+            vec![registration::resume::set(Some(format!(
+                "{}/{file_name}",
+                registration.hackathon_id
+            )))],
+        )
+        .exec()
+        .await
+    {
+        Ok(_) => Ok((StatusCode::CREATED, ())),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    get,
+    context_path = "/registrations",
+    path = "/{id}/resume",
+    responses(
+        (status = 200, description = "Streams the resume attached to registration with id", content_type = "application/pdf"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not the owner of the registration and missing resumes:read"),
+        (status = 404, description = "Registration or resume not found")
+    ),
+    params(("id" = String, Path, description = "id of registration whose resume to download")),
+    security(
+        ("api_key" = [])
+    )
+)]
+pub async fn download_resume_by_registration_id(
+    State(app_state): State<AppState>,
+    Path(id): Path<String>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Response, ApiError> {
+    let registration = find_registration(&app_state, id).await?;
+
+    owner_check(&registration, &request_user, Permission::ResumesRead)?;
+
+    let object_name = registration
+        .resume
+        .ok_or(ApiError::NotFound("No resume uploaded".to_owned()))?;
+
+    let body = stream_file(Buckets::RESUME, None, &object_name, app_state)
+        .await
+        .map_err(|(message, status_code)| ApiError::from_status(status_code, message))?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, PDF_CONTENT_TYPE.to_owned()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}.pdf\"", registration.id),
+            ),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+pub fn registration_get_router(app_state: AppState) -> Router {
+    Router::new()
+        .route("/", get(get_all_registrations).post(create_registration))
+        .route("/me", get(get_my_registration))
+        .route(
+            "/me/resume",
+            // Leave headroom above the resume limit for the multipart boundaries
+            post(upload_my_resume).layer(DefaultBodyLimit::max(MAX_RESUME_SIZE + 64 * 1024)),
+        )
+        .route(
+            "/:id",
+            get(get_registration_by_id)
+                .patch(update_registration_by_id)
+                .delete(withdraw_registration_by_id),
+        )
+        .route("/:id/resume", get(download_resume_by_registration_id))
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            auth_guard::require_auth,
+        ))
+        .with_state(app_state)
+}
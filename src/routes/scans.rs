@@ -1,29 +1,170 @@
+use std::collections::BTreeMap;
+
 use axum::{
     extract::{Path, State},
+    middleware,
     routing::get,
     Json, Router,
 };
-use hyper::StatusCode;
-use serde::Deserialize;
-use utoipa::ToSchema;
+use chrono::{DateTime, Duration, FixedOffset, TimeZone, Utc};
+use prisma_client_rust::Direction;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
+use validator::Validate;
 
 use crate::{
+    api_error::ApiError,
+    auth_guard::{self, RequirePermission},
     base_types::AppState,
-    prisma::{event, scan},
+    pagination::{Page, PageParams, ScanPage, SortOrder},
+    permissions::require,
+    prisma::{event, scan, ScanType},
+    routes::hackathons::{find_hackathon, HackathonScope},
+    validation::ValidQuery,
 };
 
-pub async fn get_all_scans(
-    State(app_state): State<AppState>,
-) -> Result<Json<Vec<scan::Data>>, StatusCode> {
-    match app_state.client.scan().find_many(vec![]).exec().await {
-        Ok(scans) => Ok(Json(scans)),
-        Err(_) => Err(StatusCode::BAD_REQUEST),
+impl<'__s> utoipa::ToSchema<'__s> for ScanType {
+    fn schema() -> (
+        &'__s str,
+        utoipa::openapi::RefOr<utoipa::openapi::schema::Schema>,
+    ) {
+        (
+            "ScanType",
+            utoipa::openapi::ObjectBuilder::new()
+                .schema_type(utoipa::openapi::SchemaType::String)
+                .enum_values::<[&str; 2usize], &str>(Some(["CHECK_IN", "CHECK_OUT"]))
+                .into(),
+        )
+    }
+
+    fn aliases() -> Vec<(&'__s str, utoipa::openapi::schema::Schema)> {
+        vec![]
     }
 }
 
+async fn find_scans_page(
+    app_state: &AppState,
+    page: &PageParams,
+    filters: Vec<scan::WhereParam>,
+) -> Result<Page<scan::Data>, ApiError> {
+    let direction = || page.direction(SortOrder::Asc);
+    let mut query = app_state
+        .client
+        .scan()
+        .find_many(filters.clone())
+        .order_by(scan::created_at::order(direction()))
+        .order_by(scan::id::order(direction()))
+        .skip(page.skip())
+        .take(page.take());
+    if let Some(cursor) = &page.cursor {
+        query = query.cursor(scan::UniqueWhereParam::IdEquals(cursor.clone()));
+    }
+
+    let (scans, total) = app_state
+        .client
+        ._batch((query, app_state.client.scan().count(filters)))
+        .await?;
+    Ok(page.page(scans, total, |scan| scan.id.clone()))
+}
+
 #[derive(Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
+#[allow(unused)]
+pub struct ScanEntity {
+    id: String,
+    event_id: String,
+    registration_id: String,
+    organizer_id: String,
+    hackathon_id: String,
+    r#type: ScanType,
+    created_at: Option<DateTime<FixedOffset>>,
+    device_id: Option<String>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    notes: Option<String>,
+}
+
+#[derive(Deserialize, IntoParams, Validate)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct ScanParams {
+    /// Only scans at this event
+    event_id: Option<String>,
+}
+
+impl ScanParams {
+    fn filters(self, hackathon_id: String) -> Vec<scan::WhereParam> {
+        let mut filters = vec![scan::hackathon_id::equals(hackathon_id)];
+        if let Some(event_id) = self.event_id {
+            filters.push(scan::event_id::equals(event_id));
+        }
+        filters
+    }
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    get,
+    context_path = "/scans",
+    path = "",
+    responses(
+        (status = 200, description = "Returns a page of a hackathon's scans, oldest first", body = ScanPage),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "No active hackathon"),
+        (status = 422, description = "Invalid query parameters")
+    ),
+    params(PageParams, HackathonScope, ScanParams),
+    security(
+        ("api_key" = ["scans:read"])
+    )
+)]
+pub async fn get_all_scans(
+    State(app_state): State<AppState>,
+    _: RequirePermission<require::ScansRead>,
+    ValidQuery(page): ValidQuery<PageParams>,
+    ValidQuery(scope): ValidQuery<HackathonScope>,
+    ValidQuery(params): ValidQuery<ScanParams>,
+) -> Result<Json<Page<scan::Data>>, ApiError> {
+    let filters = params.filters(scope.resolve(&app_state).await?);
+    Ok(Json(find_scans_page(&app_state, &page, filters).await?))
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    get,
+    context_path = "/hackathons",
+    path = "/{id}/scans",
+    responses(
+        (status = 200, description = "Returns a page of a hackathon's scans, oldest first", body = ScanPage),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "No hackathon found"),
+        (status = 422, description = "Invalid query parameters")
+    ),
+    params(
+        ("id" = String, Path, description = "id of hackathon whose scans to get"),
+        PageParams,
+        ScanParams
+    ),
+    security(
+        ("api_key" = ["scans:read"])
+    )
+)]
+pub async fn get_hackathon_scans(
+    State(app_state): State<AppState>,
+    _: RequirePermission<require::ScansRead>,
+    Path(id): Path<String>,
+    ValidQuery(page): ValidQuery<PageParams>,
+    ValidQuery(params): ValidQuery<ScanParams>,
+) -> Result<Json<Page<scan::Data>>, ApiError> {
+    let filters = params.filters(find_hackathon(&app_state, id).await?.id);
+    Ok(Json(find_scans_page(&app_state, &page, filters).await?))
+}
+
+// Extracted from the path, so the fields are named after its segments
+#[derive(Deserialize, ToSchema)]
 pub struct ScanIdEntity {
     event_id: Uuid,
     registration_id: Uuid,
@@ -31,101 +172,236 @@ pub struct ScanIdEntity {
 
 pub async fn get_scan_by_id(
     State(app_state): State<AppState>,
+    _: RequirePermission<require::ScansRead>,
     Path(path): Path<ScanIdEntity>,
-) -> Result<Json<Vec<scan::Data>>, StatusCode> {
+) -> Result<Json<Vec<scan::Data>>, ApiError> {
+    // Every time the hacker came and went, oldest first
     match app_state
         .client
         .scan()
-        .find_unique(scan::UniqueWhereParam::EventIdRegistrationIdEquals(
-            path.event_id.to_string(),
-            path.registration_id.to_string(),
-        ))
+        .find_many(vec![
+            scan::event_id::equals(path.event_id.to_string()),
+            scan::registration_id::equals(path.registration_id.to_string()),
+        ])
+        .order_by(scan::created_at::order(Direction::Asc))
+        .order_by(scan::r#type::order(Direction::Asc))
         .exec()
         .await
     {
-        Ok(scan) => match scan {
-            Some(scan) => Ok(Json(vec![scan])),
-            None => Err(StatusCode::NOT_FOUND),
-        },
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        Ok(scans) if scans.is_empty() => Err(ApiError::NotFound("Scan not found".to_owned())),
+        Ok(scans) => Ok(Json(scans)),
+        Err(err) => Err(err.into()),
     }
 }
 
 pub async fn get_scans_by_organizer_id(
     State(app_state): State<AppState>,
+    _: RequirePermission<require::ScansRead>,
     Path(id): Path<Uuid>,
-) -> Result<Json<Vec<scan::Data>>, StatusCode> {
-    match app_state
-        .client
-        .scan()
-        .find_many(vec![scan::organizer_id::equals(id.to_string())])
-        .exec()
-        .await
-    {
-        Ok(scans) => Ok(Json(scans)),
-        Err(_) => Err(StatusCode::BAD_REQUEST),
-    }
+    ValidQuery(page): ValidQuery<PageParams>,
+    ValidQuery(scope): ValidQuery<HackathonScope>,
+) -> Result<Json<Page<scan::Data>>, ApiError> {
+    let filters = vec![
+        scan::organizer_id::equals(id.to_string()),
+        scan::hackathon_id::equals(scope.resolve(&app_state).await?),
+    ];
+    Ok(Json(find_scans_page(&app_state, &page, filters).await?))
 }
 
 pub async fn get_scans_by_registration_id(
     State(app_state): State<AppState>,
+    _: RequirePermission<require::ScansRead>,
     Path(id): Path<Uuid>,
-) -> Result<Json<Vec<scan::Data>>, StatusCode> {
-    match app_state
+    ValidQuery(page): ValidQuery<PageParams>,
+) -> Result<Json<Page<scan::Data>>, ApiError> {
+    let filters = vec![scan::registration_id::equals(id.to_string())];
+    Ok(Json(find_scans_page(&app_state, &page, filters).await?))
+}
+
+/// How many scans an event has. The scans themselves are paged through with `GET /scans?eventId=`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EventScanCount {
+    pub event_id: String,
+    pub name: String,
+    pub scan_count: i64,
+}
+
+async fn count_event_scans(
+    app_state: &AppState,
+    events: Vec<event::Data>,
+) -> Result<Vec<EventScanCount>, ApiError> {
+    let counts = app_state
         .client
-        .scan()
-        .find_many(vec![scan::registration_id::equals(id.to_string())])
-        .exec()
-        .await
-    {
-        Ok(scans) => Ok(Json(scans)),
-        Err(_) => Err(StatusCode::BAD_REQUEST),
-    }
+        ._batch(
+            events
+                .iter()
+                .map(|event| {
+                    app_state
+                        .client
+                        .scan()
+                        .count(vec![scan::event_id::equals(event.id.clone())])
+                })
+                .collect::<Vec<_>>(),
+        )
+        .await?;
+    Ok(events
+        .into_iter()
+        .zip(counts)
+        .map(|(event, scan_count)| EventScanCount {
+            event_id: event.id,
+            name: event.name,
+            scan_count,
+        })
+        .collect())
 }
 
 pub async fn get_all_events_with_scans(
     State(app_state): State<AppState>,
-) -> Result<Json<Vec<event::Data>>, StatusCode> {
-    match app_state
+    _: RequirePermission<require::ScansRead>,
+    ValidQuery(scope): ValidQuery<HackathonScope>,
+) -> Result<Json<Vec<EventScanCount>>, ApiError> {
+    let hackathon_id = scope.resolve(&app_state).await?;
+    let events = app_state
         .client
         .event()
-        .find_many(vec![])
-        .with(event::scan::fetch(vec![]))
+        .find_many(vec![event::hackathon_id::equals(hackathon_id)])
         .exec()
-        .await
-    {
-        Ok(events) => Ok(Json(events)),
-        Err(_) => Err(StatusCode::BAD_REQUEST),
-    }
+        .await?;
+    Ok(Json(count_event_scans(&app_state, events).await?))
 }
 
 pub async fn get_event_with_scans_by_id(
     State(app_state): State<AppState>,
+    _: RequirePermission<require::ScansRead>,
     Path(id): Path<Uuid>,
-) -> Result<Json<Vec<event::Data>>, StatusCode> {
-    match app_state
+) -> Result<Json<EventScanCount>, ApiError> {
+    let event = app_state
         .client
         .event()
         .find_unique(event::UniqueWhereParam::IdEquals(id.to_string()))
-        .with(event::scan::fetch(vec![]))
         .exec()
-        .await
-    {
-        Ok(event) => match event {
-            Some(event) => Ok(Json(vec![event])),
-            None => Err(StatusCode::NOT_FOUND),
-        },
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        .await?
+        .ok_or(ApiError::NotFound("Event not found".to_owned()))?;
+    let mut counts = count_event_scans(&app_state, vec![event]).await?;
+    Ok(Json(counts.remove(0)))
+}
+
+pub const DEFAULT_WINDOW_MINUTES: i64 = 60;
+
+#[derive(Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineParams {
+    event_id: Option<String>,
+    /// Defaults to CHECK_IN
+    r#type: Option<ScanType>,
+    /// Only scans at or after this time
+    from: Option<DateTime<FixedOffset>>,
+    /// Only scans before this time
+    to: Option<DateTime<FixedOffset>>,
+    /// Minutes per bucket, up to a day. Defaults to an hour
+    #[validate(range(min = 1, max = 1440))]
+    window: Option<i64>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanBucket {
+    pub start: DateTime<Utc>,
+    pub count: i64,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanTimeline {
+    pub buckets: Vec<ScanBucket>,
+    /// Scans recorded before scan times were tracked, which aren't in any bucket
+    pub undated: i64,
+}
+
+/// Counts scans per `window`, from the window of the first scan to that of the last. Empty windows
+/// in between are kept so the timeline has no gaps, and windows are aligned to the epoch, so hourly
+/// ones start on the hour.
+pub fn bucket_scans(
+    times: impl IntoIterator<Item = Option<DateTime<Utc>>>,
+    window: Duration,
+) -> ScanTimeline {
+    let window = window.num_seconds().max(1);
+    let mut counts: BTreeMap<i64, i64> = BTreeMap::new();
+    let mut undated = 0;
+    for time in times {
+        match time {
+            Some(time) => {
+                *counts
+                    .entry(time.timestamp().div_euclid(window))
+                    .or_default() += 1;
+            }
+            None => undated += 1,
+        }
+    }
+
+    let buckets = match (counts.keys().next(), counts.keys().next_back()) {
+        (Some(&first), Some(&last)) => (first..=last)
+            .filter_map(|index| {
+                Some(ScanBucket {
+                    start: Utc.timestamp_opt(index * window, 0).single()?,
+                    count: counts.get(&index).copied().unwrap_or(0),
+                })
+            })
+            .collect(),
+        _ => vec![],
+    };
+    ScanTimeline { buckets, undated }
+}
+
+pub async fn get_scan_timeline(
+    State(app_state): State<AppState>,
+    _: RequirePermission<require::ScansRead>,
+    ValidQuery(scope): ValidQuery<HackathonScope>,
+    ValidQuery(params): ValidQuery<TimelineParams>,
+) -> Result<Json<ScanTimeline>, ApiError> {
+    let mut filters = vec![
+        scan::hackathon_id::equals(scope.resolve(&app_state).await?),
+        scan::r#type::equals(params.r#type.unwrap_or(ScanType::CheckIn)),
+    ];
+    if let Some(event_id) = params.event_id {
+        filters.push(scan::event_id::equals(event_id));
+    }
+    if let Some(from) = params.from {
+        filters.push(scan::created_at::gte(from));
+    }
+    if let Some(to) = params.to {
+        filters.push(scan::created_at::lt(to));
     }
+
+    let scans = app_state
+        .client
+        .scan()
+        .find_many(filters)
+        .select(scan::select!({ created_at }))
+        .exec()
+        .await?;
+    let window = Duration::minutes(params.window.unwrap_or(DEFAULT_WINDOW_MINUTES));
+    Ok(Json(bucket_scans(
+        scans
+            .into_iter()
+            .map(|scan| scan.created_at.map(|time| time.with_timezone(&Utc))),
+        window,
+    )))
 }
 
 pub fn scans_get_router(app_state: AppState) -> Router {
     Router::new()
         .route("/", get(get_all_scans))
-        .route("/:event_id/:user_id", get(get_scan_by_id))
+        .route("/:event_id/:registration_id", get(get_scan_by_id))
         .route("/analytics/organizer/:id", get(get_scans_by_organizer_id))
         .route("/analytics/user/:id", get(get_scans_by_registration_id))
         .route("/analytics/events", get(get_all_events_with_scans))
         .route("/analytics/events/:id", get(get_event_with_scans_by_id))
+        .route("/analytics/timeline", get(get_scan_timeline))
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            auth_guard::require_auth,
+        ))
         .with_state(app_state)
 }
@@ -0,0 +1,314 @@
+use axum::{
+    extract::{Path, Query, State},
+    middleware,
+    routing::{get, post, put},
+    Json, Router,
+};
+use hyper::StatusCode;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+use crate::{
+    api_error::ApiError,
+    auth_guard::{self, RequirePermission},
+    base_types::{AppState, GetResponse, UpdateResponse},
+    permissions::require,
+    prisma::{
+        project,
+        score::{self, Data, UniqueWhereParam},
+    },
+    routes::hackathons::find_active_hackathon,
+    scoring::{build_leaderboard, AggregationMode, Leaderboard, ProjectScores, ScoreCard},
+    validation::ValidJson,
+};
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[allow(unused)]
+pub struct ScoreEntity {
+    hackathon_id: String,
+    #[serde(rename = "projectID")]
+    project_id: String,
+    #[serde(rename = "judgeID")]
+    judge_id: String,
+    submitted: bool,
+    creativity: i32,
+    technicality: i32,
+    implementation: i32,
+    clarity: i32,
+    growth: i32,
+    challenge1: Option<i32>,
+    challenge2: Option<i32>,
+    challenge3: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveScoreEntity {
+    #[validate(range(min = 0, max = 5))]
+    creativity: i32,
+    #[validate(range(min = 0, max = 5))]
+    technicality: i32,
+    #[validate(range(min = 0, max = 5))]
+    implementation: i32,
+    #[validate(range(min = 0, max = 5))]
+    clarity: i32,
+    #[validate(range(min = 0, max = 5))]
+    growth: i32,
+    /// Left out when the project isn't entered in the challenge
+    #[serde(rename = "challenge1")]
+    #[validate(range(min = 0, max = 5))]
+    challenge_1: Option<i32>,
+    /// Left out when the project isn't entered in the challenge
+    #[serde(rename = "challenge2")]
+    #[validate(range(min = 0, max = 5))]
+    challenge_2: Option<i32>,
+    /// Left out when the project isn't entered in the challenge
+    #[serde(rename = "challenge3")]
+    #[validate(range(min = 0, max = 5))]
+    challenge_3: Option<i32>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderboardParams {
+    /// Defaults to the active hackathon
+    hackathon_id: Option<String>,
+    /// Defaults to raw averages
+    #[serde(default)]
+    mode: AggregationMode,
+}
+
+async fn find_assigned_score(
+    app_state: &AppState,
+    project_id: String,
+    judge_id: String,
+) -> Result<Data, ApiError> {
+    match app_state
+        .client
+        .score()
+        .find_unique(UniqueWhereParam::ProjectIdJudgeIdEquals(
+            project_id, judge_id,
+        ))
+        .exec()
+        .await
+    {
+        Ok(Some(score)) => Ok(score),
+        Ok(None) => Err(ApiError::NotFound(
+            "You are not assigned to this project".to_owned(),
+        )),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Updates the score unless it has been submitted. That's checked in the same statement, so a save
+/// racing a submit can't change the score after it's locked.
+async fn update_unsubmitted_score(
+    app_state: &AppState,
+    score: Data,
+    params: Vec<score::SetParam>,
+) -> Result<(), ApiError> {
+    let updated = app_state
+        .client
+        .score()
+        .update_many(
+            vec![
+                score::project_id::equals(score.project_id),
+                score::judge_id::equals(score.judge_id),
+                score::submitted::equals(false),
+            ],
+            params,
+        )
+        .exec()
+        .await?;
+
+    if updated == 0 {
+        Err(ApiError::Conflict(
+            "Score has already been submitted".to_owned(),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    get,
+    context_path = "/scores",
+    path = "/me",
+    responses(
+        (status = 200, description = "Returns the current judge's assigned projects and scores for the active hackathon", body = [ScoreEntity]),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "No active hackathon")
+    ),
+    security(
+        ("api_key" = ["scores:write"])
+    )
+)]
+pub async fn get_my_scores(
+    State(app_state): State<AppState>,
+    RequirePermission(request_user, _): RequirePermission<require::ScoresWrite>,
+) -> GetResponse<Json<Vec<Data>>> {
+    let hackathon = find_active_hackathon(&app_state).await?;
+
+    match app_state
+        .client
+        .score()
+        .find_many(vec![
+            score::judge_id::equals(request_user.id),
+            score::hackathon_id::equals(hackathon.id),
+        ])
+        .with(score::project::fetch())
+        .exec()
+        .await
+    {
+        Ok(scores) => Ok((StatusCode::OK, Json(scores))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    put,
+    context_path = "/scores",
+    path = "/{project_id}",
+    responses(
+        (status = 200, description = "Saved draft score for project"),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Judge is not assigned to project"),
+        (status = 409, description = "Score has already been submitted"),
+        (status = 422, description = "Criteria scores must be between 0 and 5")
+    ),
+    params(("project_id" = String, Path, description = "id of project being scored")),
+    request_body = SaveScoreEntity,
+    security(
+        ("api_key" = ["scores:write"])
+    )
+)]
+pub async fn save_score(
+    State(app_state): State<AppState>,
+    Path(project_id): Path<String>,
+    RequirePermission(request_user, _): RequirePermission<require::ScoresWrite>,
+    ValidJson(body): ValidJson<SaveScoreEntity>,
+) -> UpdateResponse {
+    let score = find_assigned_score(&app_state, project_id, request_user.id).await?;
+    update_unsubmitted_score(
+        &app_state,
+        score,
+        vec![
+            score::creativity::set(body.creativity),
+            score::technicality::set(body.technicality),
+            score::implementation::set(body.implementation),
+            score::clarity::set(body.clarity),
+            score::growth::set(body.growth),
+            score::challenge_1::set(body.challenge_1),
+            score::challenge_2::set(body.challenge_2),
+            score::challenge_3::set(body.challenge_3),
+        ],
+    )
+    .await?;
+    Ok((StatusCode::OK, ()))
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    post,
+    context_path = "/scores",
+    path = "/{project_id}/submit",
+    responses(
+        (status = 200, description = "Submitted score for project, locking further edits"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Judge is not assigned to project"),
+        (status = 409, description = "Score has already been submitted")
+    ),
+    params(("project_id" = String, Path, description = "id of project being scored")),
+    security(
+        ("api_key" = ["scores:write"])
+    )
+)]
+pub async fn submit_score(
+    State(app_state): State<AppState>,
+    Path(project_id): Path<String>,
+    RequirePermission(request_user, _): RequirePermission<require::ScoresWrite>,
+) -> UpdateResponse {
+    let score = find_assigned_score(&app_state, project_id, request_user.id).await?;
+    update_unsubmitted_score(&app_state, score, vec![score::submitted::set(true)]).await?;
+    Ok((StatusCode::OK, ()))
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    get,
+    context_path = "/scores",
+    path = "/leaderboard",
+    responses(
+        (status = 200, description = "Returns projects ranked by their aggregated submitted scores, overall and per challenge", body = Leaderboard),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "No active hackathon")
+    ),
+    params(LeaderboardParams),
+    security(
+        ("api_key" = ["scores:read"])
+    )
+)]
+pub async fn get_leaderboard(
+    State(app_state): State<AppState>,
+    Query(params): Query<LeaderboardParams>,
+    _: RequirePermission<require::ScoresRead>,
+) -> GetResponse<Json<Leaderboard>> {
+    let hackathon_id = match params.hackathon_id {
+        Some(hackathon_id) => hackathon_id,
+        None => find_active_hackathon(&app_state).await?.id,
+    };
+
+    let projects = match app_state
+        .client
+        .project()
+        .find_many(vec![project::hackathon_id::equals(hackathon_id)])
+        .with(project::score::fetch(vec![score::submitted::equals(true)]))
+        .exec()
+        .await
+    {
+        Ok(projects) => projects,
+        Err(err) => return Err(err.into()),
+    };
+
+    let projects = projects
+        .into_iter()
+        .map(|project| ProjectScores {
+            cards: project
+                .score
+                .as_deref()
+                .unwrap_or_default()
+                .iter()
+                .map(ScoreCard::from)
+                .collect(),
+            project_id: project.id,
+            project_name: project.name,
+        })
+        .collect();
+
+    Ok((
+        StatusCode::OK,
+        Json(build_leaderboard(projects, params.mode)),
+    ))
+}
+
+pub fn score_get_router(app_state: AppState) -> Router {
+    Router::new()
+        .route("/me", get(get_my_scores))
+        .route("/leaderboard", get(get_leaderboard))
+        .route("/:project_id", put(save_score))
+        .route("/:project_id/submit", post(submit_score))
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            auth_guard::require_auth,
+        ))
+        .with_state(app_state)
+}
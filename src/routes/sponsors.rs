@@ -1,43 +1,80 @@
 use axum::{
-    extract::{Json, State},
-    routing::post,
+    extract::{Json, Path, State},
+    middleware,
+    routing::{get, patch, post},
     Router,
 };
-use axum_valid::Valid;
 use hyper::StatusCode;
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
-use validator::Validate;
+use validator::{validate_url, Validate, ValidationErrors};
 
 use crate::{
-    base_types::AppState,
+    api_error::ApiError,
+    audit::{self, snapshot},
+    auth_guard::{self, RequirePermission},
+    base_types::{AppState, CreateResponse, GetResponse, UpdateResponse},
+    docs::{partial, partial_schema},
+    pagination::{Page, PageParams, SortOrder, SponsorPage},
+    permissions::require,
     prisma::{
         hackathon::UniqueWhereParam,
         sponsor::{self, Data},
-        SponsorLevel,
+        AuditAction, SponsorLevel,
     },
+    routes::hackathons::{find_hackathon, HackathonScope},
+    validation::{invalid_field, PartialChecks, ValidJson, ValidQuery},
 };
 
 #[derive(Serialize, Deserialize, ToSchema, Validate)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateSponsorEntity {
+    #[validate(length(min = 1))]
     name: String,
     level: SponsorLevel,
     #[validate(url)]
     link: Option<String>,
+    #[validate(length(min = 1))]
     dark_logo: String,
+    #[validate(length(min = 1))]
     light_logo: String,
+    #[validate(range(min = 0))]
     order: i32,
     hackathon_id: Uuid,
 }
 
 #[axum::debug_handler]
+#[utoipa::path(
+    post,
+    context_path = "/sponsors",
+    path = "",
+    responses(
+        (status = 201, description = "Created a new sponsor"),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 422, description = "Invalid sponsor")
+    ),
+    request_body = CreateSponsorEntity,
+    security(
+        ("api_key" = ["hackathon:write"])
+    )
+)]
 pub async fn create_sponsor(
-    State(state): State<AppState>,
-    Valid(Json(body)): Valid<Json<CreateSponsorEntity>>,
-) -> Result<String, (StatusCode, String)> {
-    match state
+    State(app_state): State<AppState>,
+    RequirePermission(request_user, _): RequirePermission<require::HackathonWrite>,
+    ValidJson(body): ValidJson<CreateSponsorEntity>,
+) -> CreateResponse {
+    app_state
+        .client
+        .hackathon()
+        .find_unique(UniqueWhereParam::IdEquals(body.hackathon_id.to_string()))
+        .exec()
+        .await?
+        .ok_or_else(|| invalid_field("hackathonId", "not_found", "No hackathon with this id"))?;
+
+    match app_state
         .client
         .sponsor()
         .create(
@@ -52,23 +89,243 @@ pub async fn create_sponsor(
         .exec()
         .await
     {
-        Ok(_sponsor) => Ok("Created sponsor successfully".to_owned()),
-        Err(err) => Err((StatusCode::BAD_REQUEST, err.to_string())),
+        Ok(sponsor) => {
+            audit::record(
+                &app_state,
+                &request_user,
+                AuditAction::Create,
+                "sponsor",
+                sponsor.id.clone(),
+                None,
+                snapshot(&sponsor),
+            )
+            .await;
+            Ok((StatusCode::CREATED, ()))
+        }
+        Err(err) => Err(err.into()),
     }
 }
 
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[allow(unused)]
+pub struct SponsorEntity {
+    id: String,
+    name: String,
+    level: SponsorLevel,
+    link: Option<String>,
+    dark_logo: String,
+    light_logo: String,
+    order: i32,
+    hackathon_id: String,
+}
+
+#[derive(Deserialize, IntoParams, Validate)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct SponsorParams {
+    /// Only sponsors at this level, e.g. GOLD
+    #[param(value_type = Option<String>)]
+    level: Option<SponsorLevel>,
+}
+
 #[axum::debug_handler]
+#[utoipa::path(
+    get,
+    context_path = "/sponsors",
+    path = "",
+    responses(
+        (status = 200, description = "Returns a page of a hackathon's sponsors in display order", body = SponsorPage),
+        (status = 400, description = "Bad request"),
+        (status = 404, description = "No active hackathon"),
+        (status = 422, description = "Invalid query parameters")
+    ),
+    params(PageParams, HackathonScope, SponsorParams),
+    security(
+        ()
+    )
+)]
 pub async fn get_all_sponsors(
     State(app_state): State<AppState>,
-) -> Result<Json<Vec<Data>>, String> {
-    match app_state.client.sponsor().find_many(vec![]).exec().await {
-        Ok(sponsors) => Ok(Json(sponsors)),
-        Err(_err) => Err("Error getting all sponsors".to_owned()),
+    ValidQuery(page): ValidQuery<PageParams>,
+    ValidQuery(scope): ValidQuery<HackathonScope>,
+    ValidQuery(params): ValidQuery<SponsorParams>,
+) -> GetResponse<Json<Page<Data>>> {
+    let hackathon_id = scope.resolve(&app_state).await?;
+    let sponsors = find_sponsors_page(&app_state, hackathon_id, &page, &params).await?;
+    Ok((StatusCode::OK, Json(sponsors)))
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    get,
+    context_path = "/hackathons",
+    path = "/{id}/sponsors",
+    responses(
+        (status = 200, description = "Returns a page of a hackathon's sponsors in display order", body = SponsorPage),
+        (status = 400, description = "Bad request"),
+        (status = 404, description = "No hackathon found"),
+        (status = 422, description = "Invalid query parameters")
+    ),
+    params(
+        ("id" = String, Path, description = "id of hackathon whose sponsors to get"),
+        PageParams,
+        SponsorParams
+    ),
+    security(
+        ()
+    )
+)]
+pub async fn get_hackathon_sponsors(
+    State(app_state): State<AppState>,
+    Path(id): Path<String>,
+    ValidQuery(page): ValidQuery<PageParams>,
+    ValidQuery(params): ValidQuery<SponsorParams>,
+) -> GetResponse<Json<Page<Data>>> {
+    let hackathon_id = find_hackathon(&app_state, id).await?.id;
+    let sponsors = find_sponsors_page(&app_state, hackathon_id, &page, &params).await?;
+    Ok((StatusCode::OK, Json(sponsors)))
+}
+
+/// Sponsors come in their display `order`.
+async fn find_sponsors_page(
+    app_state: &AppState,
+    hackathon_id: String,
+    page: &PageParams,
+    params: &SponsorParams,
+) -> Result<Page<Data>, ApiError> {
+    let mut filters = vec![sponsor::hackathon_id::equals(hackathon_id)];
+    if let Some(level) = params.level {
+        filters.push(sponsor::level::equals(level));
+    }
+
+    let direction = || page.direction(SortOrder::Asc);
+    let mut query = app_state
+        .client
+        .sponsor()
+        .find_many(filters.clone())
+        .order_by(sponsor::order::order(direction()))
+        .order_by(sponsor::id::order(direction()))
+        .skip(page.skip())
+        .take(page.take());
+    if let Some(cursor) = page.cursor.clone() {
+        query = query.cursor(sponsor::UniqueWhereParam::IdEquals(cursor));
+    }
+
+    let (sponsors, total) = app_state
+        .client
+        ._batch((query, app_state.client.sponsor().count(filters)))
+        .await?;
+    Ok(page.page(sponsors, total, |sponsor| sponsor.id.clone()))
+}
+
+sponsor::partial_unchecked!(
+    SponsorUpdateEntity {
+        name
+        level
+        link
+        dark_logo
+        light_logo
+        order
+    }
+);
+
+impl Validate for SponsorUpdateEntity {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        PartialChecks::new()
+            .non_empty("name", self.name.as_ref())
+            .non_empty("dark_logo", self.dark_logo.as_ref())
+            .non_empty("light_logo", self.light_logo.as_ref())
+            .check(
+                "link",
+                "url",
+                !matches!(&self.link, Some(Some(link)) if !validate_url(link)),
+            )
+            .check(
+                "order",
+                "range",
+                !matches!(self.order, Some(order) if order < 0),
+            )
+            .finish()
+    }
+}
+
+partial_schema!(SponsorUpdateEntity {
+    "name": partial::string(),
+    "level": partial::one_of(&["PLATINUM", "GOLD", "SILVER", "BRONZE", "NONE"]),
+    "link": partial::string(),
+    "darkLogo": partial::string(),
+    "lightLogo": partial::string(),
+    "order": partial::integer(),
+});
+
+#[axum::debug_handler]
+#[utoipa::path(
+    patch,
+    path = "/sponsors/{id}",
+    responses(
+        (status = 200, description = "Updated the given fields of a sponsor by id"),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Sponsor not found"),
+        (status = 422, description = "Invalid sponsor")
+    ),
+    request_body = SponsorUpdateEntity,
+    security(
+        ("api_key" = ["hackathon:write"])
+    )
+)]
+pub async fn update_sponsor_by_id(
+    State(app_state): State<AppState>,
+    Path(id): Path<String>,
+    RequirePermission(request_user, _): RequirePermission<require::HackathonWrite>,
+    ValidJson(body): ValidJson<SponsorUpdateEntity>,
+) -> UpdateResponse {
+    let before = match app_state
+        .client
+        .sponsor()
+        .find_unique(sponsor::UniqueWhereParam::IdEquals(id.clone()))
+        .exec()
+        .await
+    {
+        Ok(Some(sponsor)) => sponsor,
+        Ok(None) => return Err(ApiError::NotFound("Sponsor not found".to_owned())),
+        Err(err) => return Err(err.into()),
+    };
+
+    match app_state
+        .client
+        .sponsor()
+        .update_unchecked(sponsor::UniqueWhereParam::IdEquals(id), body.to_params())
+        .exec()
+        .await
+    {
+        Ok(after) => {
+            audit::record(
+                &app_state,
+                &request_user,
+                AuditAction::Update,
+                "sponsor",
+                after.id.clone(),
+                snapshot(&before),
+                snapshot(&after),
+            )
+            .await;
+            Ok((StatusCode::OK, ()))
+        }
+        Err(err) => Err(err.into()),
     }
 }
 
 pub fn sponsor_get_router(app_state: AppState) -> Router {
     Router::new()
-        .route("/", post(create_sponsor).get(get_all_sponsors))
+        .route("/", post(create_sponsor))
+        .route("/:id", patch(update_sponsor_by_id))
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            auth_guard::require_auth,
+        ))
+        .route("/", get(get_all_sponsors))
         .with_state(app_state)
 }
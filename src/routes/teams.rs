@@ -0,0 +1,457 @@
+use axum::{
+    extract::State,
+    middleware,
+    routing::{get, post},
+    Extension, Json, Router,
+};
+use hyper::StatusCode;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    api_error::ApiError,
+    auth_guard::{self, RequestUser, RequirePermission},
+    base_types::{AppState, GetResponse, UpdateResponse},
+    database,
+    permissions::require,
+    prisma::{
+        hackathon,
+        project::{self, UniqueWhereParam},
+        registration, PrismaClient,
+    },
+    routes::{
+        hackathons::find_active_hackathon,
+        projects::{find_project, ProjectEntity},
+        registrations::RegistrationEntity,
+    },
+    validation::ValidJson,
+};
+
+const DEFAULT_MAX_TEAM_SIZE: usize = 4;
+
+pub fn get_max_team_size() -> usize {
+    std::env::var("MAX_TEAM_SIZE")
+        .ok()
+        .and_then(|size| size.parse().ok())
+        .unwrap_or(DEFAULT_MAX_TEAM_SIZE)
+}
+
+fn generate_join_code() -> String {
+    Uuid::new_v4().simple().to_string()[..8].to_uppercase()
+}
+
+/// A teammate as shown to the rest of the team, without the rest of their registration.
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamMemberEntity {
+    registration_id: String,
+    first_name: String,
+    last_name: String,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamEntity {
+    id: String,
+    name: String,
+    hackathon_id: String,
+    join_code: Option<String>,
+    members: Vec<TeamMemberEntity>,
+}
+
+impl TryFrom<project::Data> for TeamEntity {
+    type Error = ApiError;
+
+    /// Needs the project's registrations fetched with their users.
+    fn try_from(project: project::Data) -> Result<Self, Self::Error> {
+        let members = project
+            .registration()
+            .map_err(|err| ApiError::Internal(err.to_string()))?
+            .iter()
+            .map(|registration| {
+                let user = registration
+                    .user()
+                    .map_err(|err| ApiError::Internal(err.to_string()))?;
+                Ok(TeamMemberEntity {
+                    registration_id: registration.id.clone(),
+                    first_name: user.first_name.clone(),
+                    last_name: user.last_name.clone(),
+                })
+            })
+            .collect::<Result<_, ApiError>>()?;
+
+        Ok(Self {
+            id: project.id,
+            name: project.name,
+            hackathon_id: project.hackathon_id,
+            join_code: project.join_code,
+            members,
+        })
+    }
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateTeamEntity {
+    #[validate(length(min = 1, max = 100))]
+    name: String,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct JoinTeamEntity {
+    #[validate(length(min = 1))]
+    join_code: String,
+}
+
+async fn find_my_registration(
+    app_state: &AppState,
+    request_user: &RequestUser,
+) -> Result<registration::Data, ApiError> {
+    let hackathon = find_active_hackathon(app_state).await?;
+
+    match app_state
+        .client
+        .registration()
+        .find_unique(registration::UniqueWhereParam::UserIdHackathonIdEquals(
+            request_user.id.clone(),
+            hackathon.id,
+        ))
+        .exec()
+        .await
+    {
+        Ok(Some(registration)) => Ok(registration),
+        Ok(None) => Err(ApiError::NotFound(
+            "Not registered for the active hackathon".to_owned(),
+        )),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Puts the registration on the team unless it's already on one, which may have happened since it
+/// was read.
+async fn add_to_team(
+    client: &PrismaClient,
+    registration_id: String,
+    project_id: String,
+) -> Result<(), ApiError> {
+    let updated = client
+        .registration()
+        .update_many(
+            vec![
+                registration::id::equals(registration_id),
+                registration::project_id::equals(None),
+            ],
+            vec![registration::project_id::set(Some(project_id))],
+        )
+        .exec()
+        .await?;
+
+    if updated == 0 {
+        Err(ApiError::Conflict("Already on a team".to_owned()))
+    } else {
+        Ok(())
+    }
+}
+
+async fn create_team_with_member(
+    client: PrismaClient,
+    name: String,
+    registration: registration::Data,
+) -> Result<project::Data, ApiError> {
+    let project = client
+        .project()
+        .create(
+            name,
+            hackathon::UniqueWhereParam::IdEquals(registration.hackathon_id),
+            vec![project::join_code::set(Some(generate_join_code()))],
+        )
+        .exec()
+        .await?;
+
+    add_to_team(&client, registration.id, project.id.clone()).await?;
+    Ok(project)
+}
+
+/// The team is locked while it's counted, so concurrent joins can't take it past the maximum size.
+async fn join_team_if_not_full(
+    client: PrismaClient,
+    registration_id: String,
+    project_id: String,
+) -> Result<(), ApiError> {
+    database::lock_for_update(&client, "Project", project_id.clone()).await?;
+    let team_size = client
+        .registration()
+        .count(vec![registration::project_id::equals(Some(
+            project_id.clone(),
+        ))])
+        .exec()
+        .await?;
+
+    if usize::try_from(team_size).unwrap_or(usize::MAX) >= get_max_team_size() {
+        return Err(ApiError::Conflict("Team is full".to_owned()));
+    }
+
+    add_to_team(&client, registration_id, project_id).await
+}
+
+async fn set_team(
+    app_state: &AppState,
+    registration_id: String,
+    project_id: Option<String>,
+) -> Result<(), ApiError> {
+    match app_state
+        .client
+        .registration()
+        .update(
+            registration::UniqueWhereParam::IdEquals(registration_id),
+            vec![registration::project_id::set(project_id)],
+        )
+        .exec()
+        .await
+    {
+        Ok(_) => Ok(()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    post,
+    context_path = "/teams",
+    path = "",
+    responses(
+        (status = 201, description = "Created a team project and added the current user to it", body = ProjectEntity),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Not registered for the active hackathon"),
+        (status = 409, description = "Already on a team"),
+        (status = 422, description = "Invalid team name")
+    ),
+    request_body = CreateTeamEntity,
+    security(
+        ("api_key" = [])
+    )
+)]
+pub async fn create_team(
+    State(app_state): State<AppState>,
+    Extension(request_user): Extension<RequestUser>,
+    ValidJson(body): ValidJson<CreateTeamEntity>,
+) -> GetResponse<Json<project::Data>> {
+    let registration = find_my_registration(&app_state, &request_user).await?;
+    if registration.project_id.is_some() {
+        return Err(ApiError::Conflict("Already on a team".to_owned()));
+    }
+
+    let project = app_state
+        .client
+        ._transaction()
+        .run(|client| create_team_with_member(client, body.name, registration))
+        .await?;
+    Ok((StatusCode::CREATED, Json(project)))
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    get,
+    context_path = "/teams",
+    path = "/me",
+    responses(
+        (status = 200, description = "Returns the current user's team with its members", body = TeamEntity),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Not on a team")
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+pub async fn get_my_team(
+    State(app_state): State<AppState>,
+    Extension(request_user): Extension<RequestUser>,
+) -> GetResponse<Json<TeamEntity>> {
+    let registration = find_my_registration(&app_state, &request_user).await?;
+    let project_id = registration
+        .project_id
+        .ok_or(ApiError::NotFound("Not on a team".to_owned()))?;
+
+    match app_state
+        .client
+        .project()
+        .find_unique(UniqueWhereParam::IdEquals(project_id))
+        .with(project::registration::fetch(vec![]).with(registration::user::fetch()))
+        .exec()
+        .await
+    {
+        Ok(Some(project)) => Ok((StatusCode::OK, Json(project.try_into()?))),
+        Ok(None) => Err(ApiError::NotFound("Not on a team".to_owned())),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    post,
+    context_path = "/teams",
+    path = "/join",
+    responses(
+        (status = 200, description = "Joined the team with the join code"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "No team with that join code"),
+        (status = 409, description = "Already on a team or the team is full"),
+        (status = 422, description = "Missing join code")
+    ),
+    request_body = JoinTeamEntity,
+    security(
+        ("api_key" = [])
+    )
+)]
+pub async fn join_team(
+    State(app_state): State<AppState>,
+    Extension(request_user): Extension<RequestUser>,
+    ValidJson(body): ValidJson<JoinTeamEntity>,
+) -> UpdateResponse {
+    let registration = find_my_registration(&app_state, &request_user).await?;
+    if registration.project_id.is_some() {
+        return Err(ApiError::Conflict("Already on a team".to_owned()));
+    }
+
+    let project = match app_state
+        .client
+        .project()
+        .find_unique(UniqueWhereParam::JoinCodeEquals(
+            body.join_code.trim().to_uppercase(),
+        ))
+        .exec()
+        .await
+    {
+        Ok(Some(project)) if project.hackathon_id == registration.hackathon_id => project,
+        Ok(_) => return Err(ApiError::NotFound("No team with that join code".to_owned())),
+        Err(err) => return Err(err.into()),
+    };
+
+    app_state
+        .client
+        ._transaction()
+        .run(|client| join_team_if_not_full(client, registration.id, project.id))
+        .await?;
+    Ok((StatusCode::OK, ()))
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    post,
+    context_path = "/teams",
+    path = "/leave",
+    responses(
+        (status = 200, description = "Left the current team"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Not on a team")
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+pub async fn leave_team(
+    State(app_state): State<AppState>,
+    Extension(request_user): Extension<RequestUser>,
+) -> UpdateResponse {
+    let registration = find_my_registration(&app_state, &request_user).await?;
+    if registration.project_id.is_none() {
+        return Err(ApiError::NotFound("Not on a team".to_owned()));
+    }
+
+    set_team(&app_state, registration.id, None).await?;
+    Ok((StatusCode::OK, ()))
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    post,
+    context_path = "/teams",
+    path = "/me/join-code",
+    responses(
+        (status = 200, description = "Replaced the team's join code and returns the team", body = ProjectEntity),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Not on a team")
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+pub async fn regenerate_join_code(
+    State(app_state): State<AppState>,
+    Extension(request_user): Extension<RequestUser>,
+) -> GetResponse<Json<project::Data>> {
+    let registration = find_my_registration(&app_state, &request_user).await?;
+    let project_id = registration
+        .project_id
+        .ok_or(ApiError::NotFound("Not on a team".to_owned()))?;
+    let project = find_project(&app_state, project_id).await?;
+
+    match app_state
+        .client
+        .project()
+        .update(
+            UniqueWhereParam::IdEquals(project.id),
+            vec![project::join_code::set(Some(generate_join_code()))],
+        )
+        .exec()
+        .await
+    {
+        Ok(project) => Ok((StatusCode::OK, Json(project))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    get,
+    context_path = "/teams",
+    path = "/teamless",
+    responses(
+        (status = 200, description = "Returns registrations for the active hackathon that are not on a team", body = [RegistrationEntity]),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "No active hackathon")
+    ),
+    security(
+        ("api_key" = ["registration:read"])
+    )
+)]
+pub async fn get_teamless_registrations(
+    State(app_state): State<AppState>,
+    _: RequirePermission<require::RegistrationRead>,
+) -> GetResponse<Json<Vec<registration::Data>>> {
+    let hackathon = find_active_hackathon(&app_state).await?;
+
+    match app_state
+        .client
+        .registration()
+        .find_many(vec![
+            registration::hackathon_id::equals(hackathon.id),
+            registration::project_id::equals(None),
+        ])
+        .with(registration::user::fetch())
+        .exec()
+        .await
+    {
+        Ok(registrations) => Ok((StatusCode::OK, Json(registrations))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub fn team_get_router(app_state: AppState) -> Router {
+    Router::new()
+        .route("/", post(create_team))
+        .route("/me", get(get_my_team))
+        .route("/me/join-code", post(regenerate_join_code))
+        .route("/join", post(join_team))
+        .route("/leave", post(leave_team))
+        .route("/teamless", get(get_teamless_registrations))
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            auth_guard::require_auth,
+        ))
+        .with_state(app_state)
+}
@@ -1,8 +1,16 @@
 // Import necessary modules
 use crate::{
-    auth_guard::{self, permission_check, RequestUser},
-    base_types::{AppState, CreateResponse, DeleteResponse, GetResponse},
-    prisma::{user, Role},
+    api_error::ApiError,
+    audit::{self, snapshot},
+    auth_guard::{self, permission_check_async, RequestUser, RequirePermission},
+    auth_provider::AuthIdentity,
+    base_types::{AppState, DeleteResponse, GetResponse, UpdateResponse},
+    pagination::{Page, PageParams, SortOrder, UserPage},
+    permissions::{require, Permission},
+    prisma::{user, AuditAction, Role},
+    routes::registrations::RegistrationEntity,
+    socket,
+    validation::{ValidJson, ValidQuery},
 };
 use axum::{
     debug_handler,
@@ -12,63 +20,233 @@ use axum::{
     Json, Router,
 };
 use hyper::StatusCode;
+use prisma_client_rust::prisma_errors::query_engine::UniqueKeyViolation;
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
 
 // User entity for serialization/deserialization
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[allow(unused)]
 pub struct UserEntity {
     id: String,
     first_name: String,
     last_name: String,
     email: String,
     gcp_id: String,
+    #[schema(value_type = String)]
+    privilege: Role,
 }
 
-// Define a struct for creating a new user
-#[derive(Deserialize, Serialize)]
-pub struct CreateUserEntity {
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterUserEntity {
+    #[validate(length(min = 1, max = 100))]
+    first_name: String,
+    #[validate(length(min = 1, max = 100))]
+    last_name: String,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateProfileEntity {
+    #[validate(length(min = 1, max = 100))]
+    first_name: Option<String>,
+    #[validate(length(min = 1, max = 100))]
+    last_name: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[allow(unused)]
+pub struct ProfileEntity {
+    id: String,
     first_name: String,
     last_name: String,
     email: String,
     gcp_id: String,
+    #[schema(value_type = String)]
+    privilege: Role,
+    #[serde(rename = "Registration")]
+    registration: Vec<RegistrationEntity>,
 }
 
-// Route handler to create a new user
-#[debug_handler]
-async fn create_user(
+#[axum::debug_handler]
+#[utoipa::path(
+    post,
+    context_path = "/users",
+    path = "/me",
+    responses(
+        (status = 201, description = "Created the user the token belongs to, with the token's uid and email", body = ProfileEntity),
+        (status = 400, description = "The token has no verified email"),
+        (status = 401, description = "Unauthorized"),
+        (status = 409, description = "A user already exists for this account or email"),
+        (status = 422, description = "Invalid name")
+    ),
+    request_body = RegisterUserEntity,
+    security(
+        ("api_key" = [])
+    )
+)]
+pub async fn register_me(
     State(app_state): State<AppState>,
-    Json(data): Json<CreateUserEntity>,
-) -> CreateResponse {
-    // Database operation
+    Extension(identity): Extension<AuthIdentity>,
+    ValidJson(body): ValidJson<RegisterUserEntity>,
+) -> GetResponse<Json<user::Data>> {
+    let email = identity.email.ok_or(ApiError::BadRequest(
+        "Account has no verified email".to_owned(),
+    ))?;
+
     match app_state
         .client
         .user()
-        .create(
-            data.first_name,
-            data.last_name,
-            data.email,
-            data.gcp_id,
-            vec![],
-        )
+        .create(body.first_name, body.last_name, email, identity.uid, vec![])
         .exec()
         .await
     {
-        Ok(_) => Ok((StatusCode::CREATED, ())),
-        Err(err) => Err((StatusCode::INTERNAL_SERVER_ERROR, err.to_string())),
+        Ok(user) => Ok((StatusCode::CREATED, Json(user))),
+        Err(err) if err.is_prisma_error::<UniqueKeyViolation>() => Err(ApiError::Conflict(
+            "A user already exists for this account or email".to_owned(),
+        )),
+        Err(err) => Err(err.into()),
     }
 }
-#[debug_handler]
-async fn get_all_users(
+
+#[axum::debug_handler]
+#[utoipa::path(
+    get,
+    context_path = "/users",
+    path = "/me",
+    responses(
+        (status = 200, description = "Returns the current user with their registrations", body = ProfileEntity),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+pub async fn get_me(
     State(app_state): State<AppState>,
     Extension(request_user): Extension<RequestUser>,
-) -> GetResponse<Json<Vec<user::Data>>> {
-    if !(permission_check(request_user, Role::Team, vec![])) {
-        return Err((StatusCode::UNAUTHORIZED, "Unauthorized".to_owned()));
+) -> GetResponse<Json<user::Data>> {
+    match app_state
+        .client
+        .user()
+        .find_unique(user::UniqueWhereParam::IdEquals(request_user.id))
+        .with(user::registration::fetch(vec![]))
+        .exec()
+        .await
+    {
+        Ok(Some(user)) => Ok((StatusCode::OK, Json(user))),
+        Ok(None) => Err(ApiError::NotFound("User not found".to_owned())),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[axum::debug_handler]
+#[utoipa::path(
+    patch,
+    context_path = "/users",
+    path = "/me",
+    responses(
+        (status = 200, description = "Updated the current user's name"),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 422, description = "Invalid name")
+    ),
+    request_body = UpdateProfileEntity,
+    security(
+        ("api_key" = [])
+    )
+)]
+pub async fn update_me(
+    State(app_state): State<AppState>,
+    Extension(request_user): Extension<RequestUser>,
+    ValidJson(body): ValidJson<UpdateProfileEntity>,
+) -> UpdateResponse {
+    let mut params = vec![];
+    if let Some(first_name) = body.first_name {
+        params.push(user::first_name::set(first_name));
+    }
+    if let Some(last_name) = body.last_name {
+        params.push(user::last_name::set(last_name));
+    }
+
+    match app_state
+        .client
+        .user()
+        .update(user::UniqueWhereParam::IdEquals(request_user.id), params)
+        .exec()
+        .await
+    {
+        Ok(user) => {
+            app_state.user_cache.invalidate_user(&user.id);
+            Ok((StatusCode::OK, ()))
+        }
+        Err(err) => Err(err.into()),
     }
-    // Replace with actual Prisma client logic
-    match app_state.client.user().find_many(vec![]).exec().await {
-        Ok(users) => Ok((StatusCode::OK, Json(users))),
-        Err(err) => Err((StatusCode::INTERNAL_SERVER_ERROR, err.to_string())),
+}
+
+#[derive(Deserialize, IntoParams, Validate)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct UserParams {
+    /// Only users with this privilege, e.g. TEAM
+    #[param(value_type = Option<String>)]
+    privilege: Option<Role>,
+}
+
+#[debug_handler]
+#[utoipa::path(
+    get,
+    context_path = "/users",
+    path = "",
+    responses(
+        (status = 200, description = "Returns a page of users, sorted by last name", body = UserPage),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 422, description = "Invalid query parameters")
+    ),
+    params(PageParams, UserParams),
+    security(
+        ("api_key" = ["user:read"])
+    )
+)]
+pub async fn get_all_users(
+    State(app_state): State<AppState>,
+    ValidQuery(page): ValidQuery<PageParams>,
+    ValidQuery(params): ValidQuery<UserParams>,
+    _: RequirePermission<require::UserRead>,
+) -> GetResponse<Json<Page<user::Data>>> {
+    let mut filters = vec![];
+    if let Some(privilege) = params.privilege {
+        filters.push(user::privilege::equals(privilege));
+    }
+
+    let direction = || page.direction(SortOrder::Asc);
+    let mut query = app_state
+        .client
+        .user()
+        .find_many(filters.clone())
+        .order_by(user::last_name::order(direction()))
+        .order_by(user::id::order(direction()))
+        .skip(page.skip())
+        .take(page.take());
+    if let Some(cursor) = page.cursor.clone() {
+        query = query.cursor(user::UniqueWhereParam::IdEquals(cursor));
+    }
+
+    match app_state
+        .client
+        ._batch((query, app_state.client.user().count(filters)))
+        .await
+    {
+        Ok((users, total)) => Ok((
+            StatusCode::OK,
+            Json(page.page(users, total, |user| user.id.clone())),
+        )),
+        Err(err) => Err(err.into()),
     }
 }
 
@@ -78,18 +256,11 @@ async fn get_user_by_id(
     Path(user_id): Path<String>,
     Extension(request_user): Extension<RequestUser>,
 ) -> GetResponse<Json<user::Data>> {
-    let permission_id = user_id.clone();
-
-    if !(permission_check(
-        request_user,
-        Role::Team,
-        vec![(
-            Role::None,
-            Box::new(move |user: user::Data| -> bool { permission_id == user.id }),
-        )],
-    )) {
-        return Err((StatusCode::UNAUTHORIZED, "Unauthorized".to_owned()));
-    }
+    permission_check_async(&request_user, Permission::UserRead, async {
+        user_id == request_user.id
+    })
+    .await?;
+
     match app_state
         .client
         .user()
@@ -99,9 +270,9 @@ async fn get_user_by_id(
     {
         Ok(user) => match user {
             Some(user) => Ok((StatusCode::OK, Json(user))),
-            None => Err((StatusCode::NOT_FOUND, "User not found".to_owned())),
+            None => Err(ApiError::NotFound("User not found".to_owned())),
         },
-        Err(err) => Err((StatusCode::INTERNAL_SERVER_ERROR, err.to_string())),
+        Err(err) => Err(err.into()),
     }
 }
 
@@ -109,11 +280,8 @@ async fn get_user_by_id(
 async fn delete_user_by_id(
     State(app_state): State<AppState>,
     Path(user_id): Path<String>,
-    Extension(request_user): Extension<RequestUser>,
+    RequirePermission(request_user, _): RequirePermission<require::UserDelete>,
 ) -> DeleteResponse {
-    if !(permission_check(request_user, Role::Team, vec![])) {
-        return Err((StatusCode::UNAUTHORIZED, "Unauthorized".to_owned()));
-    };
     match app_state
         .client
         .user()
@@ -121,8 +289,22 @@ async fn delete_user_by_id(
         .exec()
         .await
     {
-        Ok(_) => Ok((StatusCode::NO_CONTENT, ())),
-        Err(err) => Err((StatusCode::INTERNAL_SERVER_ERROR, err.to_string())),
+        Ok(user) => {
+            app_state.user_cache.invalidate_user(&user.id);
+            socket::disconnect_user(&app_state, &user.id);
+            audit::record(
+                &app_state,
+                &request_user,
+                AuditAction::Delete,
+                "user",
+                user.id.clone(),
+                snapshot(&user),
+                None,
+            )
+            .await;
+            Ok((StatusCode::NO_CONTENT, ()))
+        }
+        Err(err) => Err(err.into()),
     }
 }
 
@@ -130,11 +312,18 @@ async fn delete_user_by_id(
 pub fn user_get_router(app_state: AppState) -> Router {
     Router::new()
         .route("/", get(get_all_users))
+        .route("/me", get(get_me).patch(update_me))
         .route("/:id", get(get_user_by_id).delete(delete_user_by_id))
         .route_layer(middleware::from_fn_with_state(
             app_state.clone(),
             auth_guard::require_auth,
         ))
-        .route("/", post(create_user))
+        .route(
+            "/me",
+            post(register_me).route_layer(middleware::from_fn_with_state(
+                app_state.clone(),
+                auth_guard::require_token,
+            )),
+        )
         .with_state(app_state)
 }
@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::prisma::score;
+
+pub const CATEGORY_COUNT: usize = 5;
+pub const CHALLENGE_COUNT: usize = 3;
+const DIMENSION_COUNT: usize = CATEGORY_COUNT + CHALLENGE_COUNT;
+
+/// One judge's submitted marks for a project, in a shape that is cheap to aggregate.
+#[derive(Clone, Debug)]
+pub struct ScoreCard {
+    pub judge_id: String,
+    /// creativity, technicality, implementation, clarity, growth
+    pub categories: [f64; CATEGORY_COUNT],
+    /// `None` for a challenge the project wasn't marked for, which leaves it out of that
+    /// challenge's ranking
+    pub challenges: [Option<f64>; CHALLENGE_COUNT],
+}
+
+impl ScoreCard {
+    fn dimension(&self, dimension: usize) -> Option<f64> {
+        if dimension < CATEGORY_COUNT {
+            Some(self.categories[dimension])
+        } else {
+            self.challenges[dimension - CATEGORY_COUNT]
+        }
+    }
+}
+
+impl From<&score::Data> for ScoreCard {
+    fn from(score: &score::Data) -> Self {
+        Self {
+            judge_id: score.judge_id.clone(),
+            categories: [
+                f64::from(score.creativity),
+                f64::from(score.technicality),
+                f64::from(score.implementation),
+                f64::from(score.clarity),
+                f64::from(score.growth),
+            ],
+            challenges: [
+                score.challenge_1.map(f64::from),
+                score.challenge_2.map(f64::from),
+                score.challenge_3.map(f64::from),
+            ],
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ProjectScores {
+    pub project_id: String,
+    pub project_name: String,
+    pub cards: Vec<ScoreCard>,
+}
+
+/// How judges' raw marks are turned into comparable numbers before averaging.
+#[derive(Deserialize, Serialize, ToSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AggregationMode {
+    /// Plain average of the marks
+    #[default]
+    Raw,
+    /// Each mark is replaced by how many standard deviations it sits from that judge's own
+    /// average, so harsh and lenient judges pull equally
+    ZScore,
+    /// Each judge's projects are ranked and awarded points from 0 (last) to 1 (first)
+    Borda,
+}
+
+#[derive(Serialize, ToSchema, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderboardEntry {
+    pub project_id: String,
+    pub project_name: String,
+    pub judge_count: usize,
+    pub creativity: f64,
+    pub technicality: f64,
+    pub implementation: f64,
+    pub clarity: f64,
+    pub growth: f64,
+    pub total: f64,
+}
+
+#[derive(Serialize, ToSchema, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChallengeEntry {
+    pub project_id: String,
+    pub project_name: String,
+    pub judge_count: usize,
+    pub score: f64,
+}
+
+#[derive(Serialize, ToSchema, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChallengeRanking {
+    /// 1, 2 or 3
+    pub challenge: usize,
+    pub entries: Vec<ChallengeEntry>,
+}
+
+#[derive(Serialize, ToSchema, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Leaderboard {
+    pub mode: AggregationMode,
+    pub overall: Vec<LeaderboardEntry>,
+    pub challenges: Vec<ChallengeRanking>,
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.iter().sum::<f64>() / values.len() as f64)
+}
+
+/// Rewrites every mark according to `mode`. The result has the same shape as the cards of each
+/// project, with `None` wherever the raw card had no mark for that dimension.
+fn adjust(
+    projects: &[ProjectScores],
+    mode: AggregationMode,
+) -> Vec<Vec<[Option<f64>; DIMENSION_COUNT]>> {
+    let mut adjusted: Vec<Vec<[Option<f64>; DIMENSION_COUNT]>> = projects
+        .iter()
+        .map(|project| {
+            project
+                .cards
+                .iter()
+                .map(|card| std::array::from_fn(|dimension| card.dimension(dimension)))
+                .collect()
+        })
+        .collect();
+
+    if mode == AggregationMode::Raw {
+        return adjusted;
+    }
+
+    // (project, card) positions of every card a judge filled in
+    let mut by_judge: HashMap<&str, Vec<(usize, usize)>> = HashMap::new();
+    for (project_index, project) in projects.iter().enumerate() {
+        for (card_index, card) in project.cards.iter().enumerate() {
+            by_judge
+                .entry(card.judge_id.as_str())
+                .or_default()
+                .push((project_index, card_index));
+        }
+    }
+
+    for positions in by_judge.values() {
+        for dimension in 0..DIMENSION_COUNT {
+            let marks: Vec<((usize, usize), f64)> = positions
+                .iter()
+                .filter_map(|&(project_index, card_index)| {
+                    adjusted[project_index][card_index][dimension]
+                        .map(|value| ((project_index, card_index), value))
+                })
+                .collect();
+
+            let rewritten = match mode {
+                AggregationMode::Raw => continue,
+                AggregationMode::ZScore => z_scores(&marks),
+                AggregationMode::Borda => borda_points(&marks),
+            };
+
+            for ((project_index, card_index), value) in rewritten {
+                adjusted[project_index][card_index][dimension] = Some(value);
+            }
+        }
+    }
+
+    adjusted
+}
+
+fn z_scores(marks: &[((usize, usize), f64)]) -> Vec<((usize, usize), f64)> {
+    let values: Vec<f64> = marks.iter().map(|(_, value)| *value).collect();
+    let Some(average) = mean(&values) else {
+        return vec![];
+    };
+    let variance = mean(
+        &values
+            .iter()
+            .map(|value| (value - average).powi(2))
+            .collect::<Vec<f64>>(),
+    )
+    .unwrap_or_default();
+    let deviation = variance.sqrt();
+
+    marks
+        .iter()
+        .map(|&(position, value)| {
+            // A judge who gave every project the same mark expressed no preference
+            let z = if deviation > f64::EPSILON {
+                (value - average) / deviation
+            } else {
+                0.0
+            };
+            (position, z)
+        })
+        .collect()
+}
+
+fn borda_points(marks: &[((usize, usize), f64)]) -> Vec<((usize, usize), f64)> {
+    if marks.len() < 2 {
+        // A single project gives no ranking information, so it sits in the middle
+        return marks.iter().map(|&(position, _)| (position, 0.5)).collect();
+    }
+    let last_place = (marks.len() - 1) as f64;
+
+    marks
+        .iter()
+        .map(|&(position, value)| {
+            // Ties share the average of the places they span
+            let beaten = marks.iter().filter(|(_, other)| *other < value).count() as f64;
+            let tied = marks.iter().filter(|(_, other)| *other == value).count() as f64;
+            (position, (beaten + (tied - 1.0) / 2.0) / last_place)
+        })
+        .collect()
+}
+
+/// Aggregates every project's submitted scores with `mode` and orders the projects by their
+/// overall average, with a separate ranking for each challenge.
+pub fn build_leaderboard(projects: Vec<ProjectScores>, mode: AggregationMode) -> Leaderboard {
+    let adjusted = adjust(&projects, mode);
+
+    let dimension_means: Vec<[(Option<f64>, usize); DIMENSION_COUNT]> = adjusted
+        .iter()
+        .map(|cards| {
+            std::array::from_fn(|dimension| {
+                let values: Vec<f64> = cards.iter().filter_map(|card| card[dimension]).collect();
+                (mean(&values), values.len())
+            })
+        })
+        .collect();
+
+    let mut overall: Vec<LeaderboardEntry> = projects
+        .iter()
+        .zip(&dimension_means)
+        .map(|(project, means)| {
+            let categories: [f64; CATEGORY_COUNT] =
+                std::array::from_fn(|dimension| means[dimension].0.unwrap_or_default());
+            LeaderboardEntry {
+                project_id: project.project_id.clone(),
+                project_name: project.project_name.clone(),
+                judge_count: project.cards.len(),
+                creativity: categories[0],
+                technicality: categories[1],
+                implementation: categories[2],
+                clarity: categories[3],
+                growth: categories[4],
+                total: mean(&categories).unwrap_or_default(),
+            }
+        })
+        .collect();
+    // Unscored projects go last regardless of mode, since a normalised 0 can be a good score
+    overall.sort_by(|a, b| {
+        (b.judge_count > 0)
+            .cmp(&(a.judge_count > 0))
+            .then(b.total.total_cmp(&a.total))
+    });
+
+    let challenges = (0..CHALLENGE_COUNT)
+        .map(|challenge| {
+            let dimension = CATEGORY_COUNT + challenge;
+            let mut entries: Vec<ChallengeEntry> = projects
+                .iter()
+                .zip(&dimension_means)
+                .filter_map(|(project, means)| {
+                    let (score, judge_count) = means[dimension];
+                    score.map(|score| ChallengeEntry {
+                        project_id: project.project_id.clone(),
+                        project_name: project.project_name.clone(),
+                        judge_count,
+                        score,
+                    })
+                })
+                .collect();
+            entries.sort_by(|a, b| b.score.total_cmp(&a.score));
+            ChallengeRanking {
+                challenge: challenge + 1,
+                entries,
+            }
+        })
+        .collect();
+
+    Leaderboard {
+        mode,
+        overall,
+        challenges,
+    }
+}
@@ -1,7 +1,35 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, FixedOffset};
+use jsonwebtoken::get_current_timestamp;
+use serde::Serialize;
 use serde_json::Value;
-use socketioxide::extract::{Data, SocketRef};
+use socketioxide::extract::{AckSender, Data, SocketRef};
+use tokio::sync::Notify;
+use utoipa::ToSchema;
+
+use crate::{
+    api_error::{ApiError, ApiErrorBody},
+    auth_guard::{authenticate_socket, forbidden, socket_token, RequestUser},
+    auth_provider::token_expiry,
+    base_types::{AppState, APP_STATE},
+    occupancy::EventOccupancy,
+    permissions::{Permission, PermissionTable},
+    prisma::{event, hackathon, scan, EventType, Role, ScanType},
+};
+
+/// The socket.io namespace clients connect to.
+pub const NAMESPACE: &str = "/socket";
 
-use crate::{auth_guard::permission_check_socket, prisma::Role};
+/// Sent with an [`ApiErrorBody`] before a connection whose handshake couldn't be authenticated is
+/// closed.
+pub const AUTH_ERROR: &str = "auth:error";
+/// Sent before a connection is closed because its token expired. Clients reconnect with a fresh
+/// token.
+pub const AUTH_EXPIRED: &str = "auth:expired";
+/// Sent before a connection is closed because its user's privilege changed or they were deleted.
+/// Clients reconnect to be joined to the rooms they're allowed now.
+pub const AUTH_REVOKED: &str = "auth:revoked";
 
 pub struct Rooms {}
 
@@ -9,30 +37,322 @@ impl Rooms {
     pub const MOBILE: &'static str = "mobile";
     pub const ADMIN: &'static str = "admin";
     pub const EXEC: &'static str = "exec";
+    pub const ALL: [&'static str; 3] = [Self::MOBILE, Self::ADMIN, Self::EXEC];
+
+    /// Checks that `role` may join `room`. The mobile room is open to every signed in user.
+    pub fn check_join(table: &PermissionTable, role: Role, room: &str) -> Result<(), ApiError> {
+        let permission = match room {
+            Self::MOBILE => return Ok(()),
+            Self::ADMIN => Permission::SocketAdmin,
+            Self::EXEC => Permission::SocketExec,
+            _ => return Err(ApiError::NotFound(format!("No room named {room}"))),
+        };
+        if table.allows(role, permission) {
+            Ok(())
+        } else {
+            Err(forbidden())
+        }
+    }
+
+    /// The rooms a user with `role` is joined to as soon as they connect.
+    pub fn for_role(table: &PermissionTable, role: Role) -> Vec<&'static str> {
+        Self::ALL
+            .into_iter()
+            .filter(|room| Self::check_join(table, role, room).is_ok())
+            .collect()
+    }
 }
 
-pub fn on_connect(socket: SocketRef, Data(_value): Data<Value>) {
+/// Acknowledges a request to join a room, with the error if it was rejected.
+#[derive(Serialize, ToSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct JoinAck {
+    pub room: String,
+    pub joined: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ApiErrorBody>,
+}
+
+/// Authenticates the handshake once, keeps the user in the socket's extensions and joins them to
+/// the rooms their role allows.
+///
+/// Clients send their bearer token as `{ "token": "..." }` in the handshake's auth payload, or in
+/// the `Authorization` header. Rooms can also be joined later with `join`, which is acknowledged
+/// with a [`JoinAck`].
+pub async fn on_connect(socket: SocketRef, Data(auth): Data<Value>) {
+    let Some(app_state) = APP_STATE.get() else {
+        socket.disconnect().ok();
+        return;
+    };
+    let user = match authenticate(&socket, &auth, app_state).await {
+        Ok(user) => user,
+        Err(err) => {
+            socket.emit(AUTH_ERROR, err.body(None)).ok();
+            socket.disconnect().ok();
+            return;
+        }
+    };
+
+    let rooms: Vec<String> = Rooms::for_role(PermissionTable::global(), user.privilege)
+        .into_iter()
+        .map(ToString::to_string)
+        .collect();
+    socket.join(rooms).ok();
+    socket.extensions.insert(user);
+
     socket.on(
-        "ping:mobile",
-        |socket: SocketRef, Data(_value): Data<String>| async move {
-            let headers = &socket.req_parts().headers;
-            if !permission_check_socket(headers.clone(), Role::None).await {
-                return;
-            }
-            socket.join(Rooms::MOBILE.to_string()).ok();
+        "join",
+        |socket: SocketRef, Data(room): Data<String>, ack: AckSender| {
+            ack.send(join(&socket, &room)).ok();
         },
     );
 
-    socket.on(
-        "ping:admin",
-        |socket: SocketRef, Data(_value): Data<String>| async move {
-            let headers = &socket.req_parts().headers;
-            if permission_check_socket(headers.clone(), Role::Exec).await {
-                socket.join(Rooms::EXEC.to_string()).ok();
-            }
-            if permission_check_socket(headers.clone(), Role::Team).await {
-                socket.join(Rooms::ADMIN.to_string()).ok();
+    // Kept for clients from before rooms were joined on connect
+    socket.on("ping:mobile", |socket: SocketRef, ack: AckSender| {
+        ack.send(join(&socket, Rooms::MOBILE)).ok();
+    });
+    socket.on("ping:admin", |socket: SocketRef, ack: AckSender| {
+        join(&socket, Rooms::EXEC);
+        ack.send(join(&socket, Rooms::ADMIN)).ok();
+    });
+}
+
+async fn authenticate(
+    socket: &SocketRef,
+    auth: &Value,
+    app_state: &AppState,
+) -> Result<RequestUser, ApiError> {
+    let token = socket_token(&socket.req_parts().headers, auth)?;
+    let user = authenticate_socket(&token, app_state).await?;
+    disconnect_on_expiry(socket.clone(), &token);
+    Ok(user)
+}
+
+/// The connection was only authenticated for as long as its token is valid, so it's closed when
+/// the token expires. The wait ends early if the client disconnects first, so the socket isn't
+/// kept around until then.
+fn disconnect_on_expiry(socket: SocketRef, token: &str) {
+    let Some(expires_at) = token_expiry(token) else {
+        return;
+    };
+    let disconnected = Arc::new(Notify::new());
+    socket.on_disconnect({
+        let disconnected = disconnected.clone();
+        move |_: SocketRef| disconnected.notify_one()
+    });
+    tokio::spawn(async move {
+        let remaining = expires_at.saturating_sub(get_current_timestamp());
+        tokio::select! {
+            () = tokio::time::sleep(Duration::from_secs(remaining)) => {
+                socket.emit(AUTH_EXPIRED, ()).ok();
+                socket.disconnect().ok();
             }
-        },
-    );
+            () = disconnected.notified() => {}
+        }
+    });
+}
+
+/// Closes every connection of the user, so one whose privilege changed or who was deleted stops
+/// receiving events for rooms they may no longer be allowed in. Called wherever the user's cached
+/// lookups are invalidated for the same reasons.
+pub fn disconnect_user(app_state: &AppState, user_id: &str) {
+    let Some(namespace) = app_state.io.of(NAMESPACE) else {
+        return;
+    };
+    let sockets = match namespace.sockets() {
+        Ok(sockets) => sockets,
+        Err(err) => {
+            tracing::error!("Failed to list sockets to disconnect user {user_id}: {err}");
+            return;
+        }
+    };
+    for socket in sockets {
+        let belongs_to_user = socket
+            .extensions
+            .get::<RequestUser>()
+            .is_some_and(|user| user.id == user_id);
+        if belongs_to_user {
+            socket.emit(AUTH_REVOKED, ()).ok();
+            socket.disconnect().ok();
+        }
+    }
+}
+
+fn join(socket: &SocketRef, room: &str) -> JoinAck {
+    let result = socket
+        .extensions
+        .get::<RequestUser>()
+        .map(|user| user.privilege)
+        .ok_or(ApiError::Unauthorized)
+        .and_then(|role| Rooms::check_join(PermissionTable::global(), role, room))
+        .and_then(|()| {
+            socket
+                .join(room.to_owned())
+                .map_err(|err| ApiError::Internal(err.to_string()))
+        });
+    JoinAck {
+        room: room.to_owned(),
+        joined: result.is_ok(),
+        error: result.err().map(|err| err.body(None)),
+    }
+}
+
+/// Every event the API pushes to clients, emitted after the change it describes has been saved.
+///
+/// | Event                     | Rooms               | Payload                 |
+/// |---------------------------|---------------------|-------------------------|
+/// | `scan:created`            | admin, exec         | [`ScanCreatedPayload`]  |
+/// | `event:created`           | mobile, admin, exec | [`EventPayload`]        |
+/// | `event:updated`           | mobile, admin, exec | [`EventPayload`]        |
+/// | `event:deleted`           | mobile, admin, exec | [`EventDeletedPayload`] |
+/// | `hackathon:activeChanged` | mobile, admin, exec | [`HackathonPayload`]    |
+/// | `occupancy:changed`       | admin               | [`EventOccupancy`]      |
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum SocketEvent {
+    ScanCreated(ScanCreatedPayload),
+    EventCreated(EventPayload),
+    EventUpdated(EventPayload),
+    EventDeleted(EventDeletedPayload),
+    ActiveHackathonChanged(HackathonPayload),
+    OccupancyChanged(EventOccupancy),
+}
+
+impl SocketEvent {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::ScanCreated(_) => "scan:created",
+            Self::EventCreated(_) => "event:created",
+            Self::EventUpdated(_) => "event:updated",
+            Self::EventDeleted(_) => "event:deleted",
+            Self::ActiveHackathonChanged(_) => "hackathon:activeChanged",
+            Self::OccupancyChanged(_) => "occupancy:changed",
+        }
+    }
+
+    /// Scans identify hackers, so they only go to organizers. Schedule changes go to everyone, and
+    /// the occupancy feed to the admin dashboard.
+    pub const fn rooms(&self) -> &'static [&'static str] {
+        match self {
+            Self::ScanCreated(_) => &[Rooms::ADMIN, Rooms::EXEC],
+            Self::OccupancyChanged(_) => &[Rooms::ADMIN],
+            Self::EventCreated(_)
+            | Self::EventUpdated(_)
+            | Self::EventDeleted(_)
+            | Self::ActiveHackathonChanged(_) => &[Rooms::MOBILE, Rooms::ADMIN, Rooms::EXEC],
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanCreatedPayload {
+    pub id: String,
+    pub event_id: String,
+    pub registration_id: String,
+    pub organizer_id: String,
+    pub hackathon_id: String,
+    pub r#type: ScanType,
+    pub created_at: Option<DateTime<FixedOffset>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<String>,
+}
+
+impl From<scan::Data> for ScanCreatedPayload {
+    fn from(scan: scan::Data) -> Self {
+        Self {
+            id: scan.id,
+            event_id: scan.event_id,
+            registration_id: scan.registration_id,
+            organizer_id: scan.organizer_id,
+            hackathon_id: scan.hackathon_id,
+            r#type: scan.r#type,
+            created_at: scan.created_at,
+            device_id: scan.device_id,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EventPayload {
+    pub id: String,
+    pub name: String,
+    pub r#type: EventType,
+    pub description: String,
+    pub location_id: String,
+    pub icon: Option<String>,
+    pub start_time: DateTime<FixedOffset>,
+    pub end_time: DateTime<FixedOffset>,
+    pub ws_presenter_names: Option<String>,
+    pub ws_relevant_skills: Option<String>,
+    pub ws_skill_level: Option<String>,
+    pub ws_urls: Option<String>,
+    pub hackathon_id: String,
+    pub capacity: Option<i32>,
+}
+
+impl From<event::Data> for EventPayload {
+    fn from(event: event::Data) -> Self {
+        Self {
+            id: event.id,
+            name: event.name,
+            r#type: event.r#type,
+            description: event.description,
+            location_id: event.location_id,
+            icon: event.icon,
+            start_time: event.start_time,
+            end_time: event.end_time,
+            ws_presenter_names: event.ws_presenter_names,
+            ws_relevant_skills: event.ws_relevant_skills,
+            ws_skill_level: event.ws_skill_level,
+            ws_urls: event.ws_urls,
+            hackathon_id: event.hackathon_id,
+            capacity: event.capacity,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EventDeletedPayload {
+    pub id: String,
+    pub hackathon_id: String,
+}
+
+#[derive(Serialize, ToSchema, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HackathonPayload {
+    pub id: String,
+    pub name: String,
+    pub start_time: DateTime<FixedOffset>,
+    pub end_time: DateTime<FixedOffset>,
+    pub active: bool,
+}
+
+impl From<hackathon::Data> for HackathonPayload {
+    fn from(hackathon: hackathon::Data) -> Self {
+        Self {
+            id: hackathon.id,
+            name: hackathon.name,
+            start_time: hackathon.start_time,
+            end_time: hackathon.end_time,
+            active: hackathon.active,
+        }
+    }
+}
+
+/// Pushes `event` to its rooms.
+///
+/// Like audit entries, this runs after the mutation has succeeded, so a failure to emit is logged
+/// rather than turned into an error for the request.
+pub fn broadcast(app_state: &AppState, event: &SocketEvent) {
+    let Some(namespace) = app_state.io.of(NAMESPACE) else {
+        tracing::error!("Socket namespace {NAMESPACE} isn't registered");
+        return;
+    };
+    let rooms: Vec<String> = event.rooms().iter().map(ToString::to_string).collect();
+    if let Err(err) = namespace.to(rooms).emit(event.name(), event) {
+        tracing::error!("Failed to broadcast {}: {err}", event.name());
+    }
 }
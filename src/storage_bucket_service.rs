@@ -1,40 +1,254 @@
-use std::path::Path;
+use std::{
+    fmt::{self, Debug},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
 
+use async_trait::async_trait;
+use axum::body::Body;
 use gcp_auth::{AuthenticationManager, CustomServiceAccount};
-use hyper::body::Bytes;
+use hyper::{body::Bytes, StatusCode};
 use urlencoding::encode;
 
-use crate::base_types::{AppState, CreateResponse};
+use crate::{
+    api_error::ApiError,
+    base_types::{AppState, CreateResponse},
+};
 
-struct Buckets;
+pub struct Buckets;
 
 impl Buckets {
     pub const RESUME: &'static str = "hackpsu_api_rust_resumes";
 }
 
+pub type StorageResult<T> = Result<T, (String, StatusCode)>;
+
+#[async_trait]
+pub trait StorageBackend: Debug + Send + Sync {
+    async fn upload(&self, bucket_name: &str, name: &str, file: Vec<u8>) -> StorageResult<()>;
+
+    async fn download(&self, bucket_name: &str, name: &str) -> StorageResult<Bytes>;
+
+    async fn stream(&self, bucket_name: &str, name: &str) -> StorageResult<Body>;
+}
+
+const FILE_PATH: &str = "./google-service-account.json";
+const GCS_SCOPES: &[&str] = &["https://www.googleapis.com/auth/devstorage.full_control"];
+const DEFAULT_LOCAL_STORAGE_DIR: &str = "./storage";
+
+pub struct GcsStorageBackend {
+    client: reqwest::Client,
+    // The manager caches the access token and only mints a new one once it is about to expire
+    authentication_manager: AuthenticationManager,
+}
+
+impl Debug for GcsStorageBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GcsStorageBackend").finish_non_exhaustive()
+    }
+}
+
+impl GcsStorageBackend {
+    pub fn from_file(path: &Path) -> Result<Self, gcp_auth::Error> {
+        let service_account = CustomServiceAccount::from_file(path)?;
+        Ok(Self {
+            client: reqwest::Client::new(),
+            authentication_manager: AuthenticationManager::from(service_account),
+        })
+    }
+
+    async fn token(&self) -> StorageResult<String> {
+        self.authentication_manager
+            .get_token(GCS_SCOPES)
+            .await
+            .map(|token| token.as_str().to_string())
+            .map_err(|err| (err.to_string(), StatusCode::INTERNAL_SERVER_ERROR))
+    }
+
+    async fn get_object(&self, bucket_name: &str, name: &str) -> StorageResult<reqwest::Response> {
+        let jwt = self.token().await?;
+
+        // https://cloud.google.com/storage/docs/json_api/v1/objects/get#request
+
+        let url = format!(
+            "https://storage.googleapis.com/storage/v1/b/{}/o/{}",
+            bucket_name,
+            // https://cloud.google.com/storage/docs/request-endpoints#encoding
+            encode(name)
+        );
+
+        let res = self
+            .client
+            .get(&url)
+            .header("Authorization", format!("Bearer {jwt}"))
+            .query(&[("alt", "media")])
+            .send()
+            .await
+            .map_err(|err| (err.to_string(), StatusCode::BAD_GATEWAY))?;
+
+        let status_code = res.status();
+
+        if status_code.is_success() {
+            Ok(res)
+        } else if status_code == reqwest::StatusCode::NOT_FOUND {
+            Err(("File not found".to_string(), StatusCode::NOT_FOUND))
+        } else {
+            Err((
+                res.text().await.unwrap_or_else(|_| {
+                    "Failed to Download File and convert error to string.".to_string()
+                }),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ))
+        }
+    }
+}
+
+#[async_trait]
+impl StorageBackend for GcsStorageBackend {
+    async fn upload(&self, bucket_name: &str, name: &str, file: Vec<u8>) -> StorageResult<()> {
+        let jwt = self.token().await?;
+
+        // https://cloud.google.com/storage/docs/json_api/v1/objects/insert#request
+
+        let url = format!("https://storage.googleapis.com/upload/storage/v1/b/{bucket_name}/o",);
+
+        let res = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("Bearer {jwt}"))
+            .header("Content-Length", file.len())
+            .query(&[("uploadType", "media"), ("name", name)])
+            .body(file)
+            .send()
+            .await
+            .map_err(|err| (err.to_string(), StatusCode::BAD_GATEWAY))?;
+
+        if res.status().is_success() {
+            Ok(())
+        } else {
+            Err((
+                res.text().await.unwrap_or_else(|_| {
+                    "Failed to Upload File and convert error to string.".to_string()
+                }),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ))
+        }
+    }
+
+    async fn download(&self, bucket_name: &str, name: &str) -> StorageResult<Bytes> {
+        self.get_object(bucket_name, name)
+            .await?
+            .bytes()
+            .await
+            .map_err(|err| (err.to_string(), StatusCode::BAD_GATEWAY))
+    }
+
+    async fn stream(&self, bucket_name: &str, name: &str) -> StorageResult<Body> {
+        let res = self.get_object(bucket_name, name).await?;
+        // Forward the object to the client as it arrives instead of buffering it in memory
+        Ok(Body::from_stream(res.bytes_stream()))
+    }
+}
+
+/// Stores objects under `<root>/<bucket>/<name>` so the API can run without Google credentials.
 #[derive(Debug)]
-pub struct StorageBucketService {
-    pub client: reqwest::Client,
+pub struct LocalStorageBackend {
+    root: PathBuf,
+}
+
+impl LocalStorageBackend {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn object_path(&self, bucket_name: &str, name: &str) -> StorageResult<PathBuf> {
+        let is_safe = |segment: &str| {
+            !segment.is_empty()
+                && Path::new(segment)
+                    .components()
+                    .all(|component| matches!(component, std::path::Component::Normal(_)))
+        };
+        if !is_safe(bucket_name) || !is_safe(name) {
+            return Err(("Invalid object name".to_string(), StatusCode::BAD_REQUEST));
+        }
+        Ok(self.root.join(bucket_name).join(name))
+    }
 }
 
-const FILE_PATH: &'static str = "./google-service-account.json";
+#[async_trait]
+impl StorageBackend for LocalStorageBackend {
+    async fn upload(&self, bucket_name: &str, name: &str, file: Vec<u8>) -> StorageResult<()> {
+        let path = self.object_path(bucket_name, name)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|err| (err.to_string(), StatusCode::INTERNAL_SERVER_ERROR))?;
+        }
+        tokio::fs::write(path, file)
+            .await
+            .map_err(|err| (err.to_string(), StatusCode::INTERNAL_SERVER_ERROR))
+    }
+
+    async fn download(&self, bucket_name: &str, name: &str) -> StorageResult<Bytes> {
+        let path = self.object_path(bucket_name, name)?;
+        match tokio::fs::read(path).await {
+            Ok(file) => Ok(Bytes::from(file)),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                Err(("File not found".to_string(), StatusCode::NOT_FOUND))
+            }
+            Err(err) => Err((err.to_string(), StatusCode::INTERNAL_SERVER_ERROR)),
+        }
+    }
+
+    async fn stream(&self, bucket_name: &str, name: &str) -> StorageResult<Body> {
+        self.download(bucket_name, name).await.map(Body::from)
+    }
+}
+
+#[derive(Debug)]
+pub struct StorageBucketService {
+    backend: Box<dyn StorageBackend>,
+}
 
 impl StorageBucketService {
+    /// Picks the backend from `STORAGE_BACKEND` (`gcs` or `local`), defaulting to `gcs`.
     pub fn new() -> Self {
-        let path = Path::new(FILE_PATH);
-        // I left this in so that we can cause a runtime error if the file is not found. This makes sure that none of the services fail silently.
-        let _service_account = CustomServiceAccount::from_file(path).unwrap();
-        let client = reqwest::Client::new();
-        Self { client }
+        match std::env::var("STORAGE_BACKEND").as_deref() {
+            Ok("local") => {
+                let root = std::env::var("LOCAL_STORAGE_DIR")
+                    .unwrap_or_else(|_| DEFAULT_LOCAL_STORAGE_DIR.to_string());
+                tracing::info!("Storing files on the local filesystem under {root}");
+                Self::with_backend(LocalStorageBackend::new(root))
+            }
+            _ => {
+                // I left this in so that we can cause a runtime error if the file is not found. This makes sure that none of the services fail silently.
+                let backend = GcsStorageBackend::from_file(Path::new(FILE_PATH)).unwrap();
+                Self::with_backend(backend)
+            }
+        }
     }
 
-    pub async fn create_jwt() -> String {
-        let path = Path::new(FILE_PATH);
-        let service_account = CustomServiceAccount::from_file(path).unwrap();
-        let authentication_manager = AuthenticationManager::from(service_account);
-        let scopes = &["https://www.googleapis.com/auth/devstorage.full_control"];
-        let token = authentication_manager.get_token(scopes).await.unwrap();
-        token.as_str().to_string()
+    pub fn with_backend(backend: impl StorageBackend + 'static) -> Self {
+        Self {
+            backend: Box::new(backend),
+        }
+    }
+
+    pub fn backend(&self) -> &dyn StorageBackend {
+        self.backend.as_ref()
+    }
+}
+
+impl Default for StorageBucketService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn object_name(folder: Option<&str>, file_name: &str) -> String {
+    match folder {
+        Some(folder) => format!("{folder}/{file_name}"),
+        None => file_name.to_string(),
     }
 }
 
@@ -45,40 +259,14 @@ pub async fn upload_file(
     file_name: &str,
     app_state: AppState,
 ) -> CreateResponse {
-    let jwt = StorageBucketService::create_jwt().await;
-
-    // https://cloud.google.com/storage/docs/json_api/v1/objects/insert#request
-
-    let url = format!("https://storage.googleapis.com/upload/storage/v1/b/{bucket_name}/o",);
-
-    let name = match folder {
-        Some(folder) => format!("{folder}/{file_name}"),
-        None => file_name.to_string(),
-    };
-
-    let res = app_state
+    match app_state
         .storage_bucket_service
-        .client
-        .post(&url)
-        .header("Authorization", format!("Bearer {jwt}"))
-        .header("Content-Length", file.len())
-        .query(&[("uploadType", "media"), ("name", &name)])
-        .body(file)
-        .send()
+        .backend()
+        .upload(bucket_name, &object_name(folder, file_name), file)
         .await
-        .unwrap();
-
-    let status_code = res.status();
-
-    if status_code.is_success() {
-        Ok((hyper::StatusCode::NO_CONTENT, ()))
-    } else {
-        Err((
-            hyper::StatusCode::INTERNAL_SERVER_ERROR,
-            res.text().await.unwrap_or_else(|_| {
-                "Failed to Upload File and convert error to string.".to_string()
-            }),
-        ))
+    {
+        Ok(()) => Ok((StatusCode::NO_CONTENT, ())),
+        Err((message, status_code)) => Err(ApiError::from_status(status_code, message)),
     }
 }
 
@@ -87,43 +275,23 @@ pub async fn download_file(
     folder: Option<&str>,
     file_name: &str,
     app_state: AppState,
-) -> Result<Bytes, (String, hyper::StatusCode)> {
-    let jwt = StorageBucketService::create_jwt().await;
-
-    // https://cloud.google.com/storage/docs/json_api/v1/objects/get#request
-
-    let name = match folder {
-        Some(folder) => format!("{folder}/{file_name}"),
-        None => file_name.to_string(),
-    };
-
-    let url = format!(
-        "https://storage.googleapis.com/storage/v1/b/{}/o/{}",
-        bucket_name,
-        // https://cloud.google.com/storage/docs/request-endpoints#encoding
-        encode(&name)
-    );
-
-    let res = app_state
+) -> StorageResult<Bytes> {
+    app_state
         .storage_bucket_service
-        .client
-        .get(&url)
-        .header("Authorization", format!("Bearer {jwt}"))
-        .query(&[("alt", "media")])
-        .send()
+        .backend()
+        .download(bucket_name, &object_name(folder, file_name))
         .await
-        .unwrap();
-
-    let status_code = res.status();
+}
 
-    if status_code.is_success() {
-        Ok(res.bytes().await.unwrap())
-    } else {
-        Err((
-            res.text().await.unwrap_or_else(|_| {
-                "Failed to Download File and convert error to string.".to_string()
-            }),
-            hyper::StatusCode::INTERNAL_SERVER_ERROR,
-        ))
-    }
+pub async fn stream_file(
+    bucket_name: &str,
+    folder: Option<&str>,
+    file_name: &str,
+    app_state: AppState,
+) -> StorageResult<Body> {
+    app_state
+        .storage_bucket_service
+        .backend()
+        .stream(bucket_name, &object_name(folder, file_name))
+        .await
 }
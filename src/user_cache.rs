@@ -0,0 +1,163 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::prisma::user;
+
+const DEFAULT_TTL: Duration = Duration::from_secs(60);
+/// Past this many entries expired ones are swept out, and if none have expired new ones are not
+/// cached until some do
+const MAX_ENTRIES: usize = 10_000;
+
+/// Users resolved from bearer tokens, so repeated requests with the same token skip the auth
+/// provider and the database.
+pub type RequestUserCache = TokenCache<user::Data>;
+
+struct Entry<V> {
+    value: V,
+    expires_at: Instant,
+}
+
+#[derive(Serialize, ToSchema, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+    /// hits / (hits + misses), or 0 before the first lookup
+    pub hit_rate: f64,
+}
+
+/// An in-memory cache keyed by the SHA-256 of a token, whose entries expire after a fixed TTL or
+/// when the token itself does, whichever comes first.
+///
+/// The TTL is also the longest a token can keep working after its user's database row changes
+/// without an explicit [`TokenCache::invalidate_where`], so it should be kept short.
+pub struct TokenCache<V> {
+    entries: Mutex<HashMap<[u8; 32], Entry<V>>>,
+    ttl: Duration,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<V> std::fmt::Debug for TokenCache<V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenCache")
+            .field("ttl", &self.ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+fn token_key(token: &str) -> [u8; 32] {
+    openssl::sha::sha256(token.as_bytes())
+}
+
+impl<V: Clone> TokenCache<V> {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            ttl,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Reads the TTL in seconds from `AUTH_CACHE_TTL_SECONDS`, defaulting to 60. A TTL of 0
+    /// turns caching off.
+    pub fn from_env() -> Self {
+        let ttl = std::env::var("AUTH_CACHE_TTL_SECONDS")
+            .ok()
+            .and_then(|seconds| seconds.parse().ok())
+            .map_or(DEFAULT_TTL, Duration::from_secs);
+        Self::new(ttl)
+    }
+
+    pub fn get(&self, token: &str) -> Option<V> {
+        let key = token_key(token);
+        let mut entries = self.entries.lock().unwrap();
+        let value = match entries.get(&key) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(entry.value.clone()),
+            Some(_) => {
+                entries.remove(&key);
+                None
+            }
+            None => None,
+        };
+
+        let counter = if value.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        value
+    }
+
+    /// Caches `value` for `token`, which expires at `token_expiry` in seconds since the epoch, its
+    /// `exp` claim.
+    pub fn insert(&self, token: &str, value: V, token_expiry: u64) {
+        let seconds_since_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let ttl = self.ttl.min(Duration::from_secs(
+            token_expiry.saturating_sub(seconds_since_epoch),
+        ));
+        if ttl.is_zero() {
+            return;
+        }
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= MAX_ENTRIES {
+            entries.retain(|_, entry| entry.expires_at > now);
+            if entries.len() >= MAX_ENTRIES {
+                return;
+            }
+        }
+        entries.insert(
+            token_key(token),
+            Entry {
+                value,
+                expires_at: now + ttl,
+            },
+        );
+    }
+
+    /// Drops every entry whose value matches, e.g. all tokens of a user whose privilege changed.
+    pub fn invalidate_where(&self, predicate: impl Fn(&V) -> bool) {
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|_, entry| !predicate(&entry.value));
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let lookups = hits + misses;
+        CacheStats {
+            hits,
+            misses,
+            entries: self.entries.lock().unwrap().len(),
+            hit_rate: if lookups == 0 {
+                0.0
+            } else {
+                hits as f64 / lookups as f64
+            },
+        }
+    }
+}
+
+impl RequestUserCache {
+    pub fn invalidate_user(&self, user_id: &str) {
+        self.invalidate_where(|user| user.id == user_id);
+    }
+}
@@ -1,17 +1,15 @@
 use std::process::exit;
 
 use axum::http::status;
-use hyper::StatusCode;
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::env;
 
+use crate::api_error::ApiError;
+
 #[axum::debug_handler]
-pub async fn handle_404() -> (StatusCode, &'static str) {
-    (
-        StatusCode::NOT_FOUND,
-        "We couldn't find the resource you requested.",
-    )
+pub async fn handle_404() -> ApiError {
+    ApiError::NotFound("We couldn't find the resource you requested.".to_owned())
 }
 
 pub async fn hello_world() -> &'static str {
@@ -39,4 +37,5 @@ pub fn get_port() -> u16 {
 
 lazy_static! {
     pub static ref UUID_VALIDATOR: Regex = Regex::new(r"[0-9a-fA-F-]{36}$").unwrap();
+    pub static ref PHONE_VALIDATOR: Regex = Regex::new(r"^\+?[0-9][0-9 ().-]{5,18}[0-9]$").unwrap();
 }
@@ -0,0 +1,271 @@
+use std::borrow::Cow;
+
+use axum::{
+    async_trait,
+    extract::{
+        rejection::{JsonRejection, QueryRejection},
+        FromRequest, FromRequestParts, Query, Request,
+    },
+    http::request::Parts,
+    Json,
+};
+use axum_valid::{Valid, ValidRejection};
+use chrono::{DateTime, FixedOffset};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+use validator::{Validate, ValidationError, ValidationErrors, ValidationErrorsKind};
+
+use crate::api_error::{ApiError, FieldError};
+
+/// Validation errors of a whole struct are reported under this key by `validator`. Errors made
+/// with [`field_error`] name the field they belong to instead.
+const SCHEMA_ERRORS_KEY: &str = "__all__";
+const FIELD_PARAM: &str = "field";
+
+/// A JSON body that's been deserialized and validated with [`Validate`], rejecting with a 422
+/// listing every failing field.
+///
+/// ```ignore
+/// async fn create_hackathon(ValidJson(body): ValidJson<CreateHackathonEntity>) { .. }
+/// ```
+pub struct ValidJson<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequest<S> for ValidJson<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned + Validate + Send,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        match Valid::<Json<T>>::from_request(request, state).await {
+            Ok(Valid(Json(body))) => Ok(Self(body)),
+            Err(ValidRejection::Valid(errors)) => Err(errors.into()),
+            Err(ValidRejection::Inner(rejection)) => Err(json_rejection(rejection)),
+        }
+    }
+}
+
+fn json_rejection(rejection: JsonRejection) -> ApiError {
+    match rejection {
+        // Well formed JSON that doesn't fit the entity, e.g. a missing field or a wrong type
+        JsonRejection::JsonDataError(err) => ApiError::Validation(vec![FieldError {
+            field: "body".to_owned(),
+            code: "invalid".to_owned(),
+            message: Some(err.body_text()),
+        }]),
+        JsonRejection::MissingJsonContentType(err) => {
+            ApiError::UnsupportedMediaType(err.body_text())
+        }
+        rejection => ApiError::BadRequest(rejection.body_text()),
+    }
+}
+
+/// Query parameters that have been deserialized and validated with [`Validate`], rejecting like
+/// [`ValidJson`].
+pub struct ValidQuery<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequestParts<S> for ValidQuery<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned + Validate + Send,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        match Valid::<Query<T>>::from_request_parts(parts, state).await {
+            Ok(Valid(Query(params))) => Ok(Self(params)),
+            Err(ValidRejection::Valid(errors)) => Err(errors.into()),
+            Err(ValidRejection::Inner(rejection)) => Err(query_rejection(rejection)),
+        }
+    }
+}
+
+fn query_rejection(rejection: QueryRejection) -> ApiError {
+    ApiError::Validation(vec![FieldError {
+        field: "query".to_owned(),
+        code: "invalid".to_owned(),
+        message: Some(rejection.body_text()),
+    }])
+}
+
+impl From<ValidationErrors> for ApiError {
+    fn from(errors: ValidationErrors) -> Self {
+        Self::Validation(field_errors(&errors))
+    }
+}
+
+/// Flattens `errors` into one entry per failing rule, with camelCase paths like
+/// `judgeIds[2]` or `address.city` to match the JSON the client sent.
+pub fn field_errors(errors: &ValidationErrors) -> Vec<FieldError> {
+    let mut field_errors = vec![];
+    collect_field_errors(errors, None, &mut field_errors);
+    field_errors.sort_by(|a, b| a.field.cmp(&b.field));
+    field_errors
+}
+
+fn collect_field_errors(
+    errors: &ValidationErrors,
+    prefix: Option<&str>,
+    out: &mut Vec<FieldError>,
+) {
+    for (field, kind) in errors.errors() {
+        let path = |name: &str| match prefix {
+            Some(prefix) => format!("{prefix}.{name}"),
+            None => name.to_owned(),
+        };
+        match kind {
+            ValidationErrorsKind::Field(errors) => {
+                for error in errors {
+                    let name = match (*field, error.params.get(FIELD_PARAM)) {
+                        (SCHEMA_ERRORS_KEY, Some(serde_json::Value::String(name))) => name.clone(),
+                        _ => camel_case(field),
+                    };
+                    out.push(FieldError {
+                        field: path(&name),
+                        code: error.code.to_string(),
+                        message: error.message.as_ref().map(ToString::to_string),
+                    });
+                }
+            }
+            ValidationErrorsKind::Struct(errors) => {
+                collect_field_errors(errors, Some(&path(&camel_case(field))), out);
+            }
+            ValidationErrorsKind::List(errors) => {
+                for (index, errors) in errors {
+                    let item = format!("{}[{index}]", path(&camel_case(field)));
+                    collect_field_errors(errors, Some(&item), out);
+                }
+            }
+        }
+    }
+}
+
+fn camel_case(field: &str) -> String {
+    let mut camel = String::with_capacity(field.len());
+    let mut upper = false;
+    for c in field.chars() {
+        if c == '_' {
+            upper = !camel.is_empty();
+        } else if upper {
+            camel.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            camel.push(c);
+        }
+    }
+    camel
+}
+
+/// An error from a struct-level (`schema`) or database-backed check that should be reported
+/// against `field`, which is the camelCase name the client sees.
+pub fn field_error(
+    field: &'static str,
+    code: &'static str,
+    message: &'static str,
+) -> ValidationError {
+    let mut error = ValidationError::new(code);
+    error.message = Some(Cow::Borrowed(message));
+    error.add_param(Cow::Borrowed(FIELD_PARAM), &field);
+    error
+}
+
+/// A 422 for an error made with [`field_error`] by a check that runs in the handler, such as
+/// [`validate_time_range`] on the merged values of a partial update.
+impl From<ValidationError> for ApiError {
+    fn from(error: ValidationError) -> Self {
+        let mut errors = ValidationErrors::new();
+        errors.add(SCHEMA_ERRORS_KEY, error);
+        errors.into()
+    }
+}
+
+/// A 422 for a single field, for checks that need the database and so run in the handler.
+pub fn invalid_field(field: &'static str, code: &'static str, message: &'static str) -> ApiError {
+    field_error(field, code, message).into()
+}
+
+/// Collects errors for the partial types generated by prisma, which can't derive [`Validate`].
+/// Fields left out of a partial update aren't being changed, so only present ones are checked.
+///
+/// ```ignore
+/// PartialChecks::new().non_empty("name", self.name.as_ref()).finish()
+/// ```
+pub struct PartialChecks(ValidationErrors);
+
+impl PartialChecks {
+    pub fn new() -> Self {
+        Self(ValidationErrors::new())
+    }
+
+    pub fn non_empty(self, field: &'static str, value: Option<&String>) -> Self {
+        self.check(
+            field,
+            "length",
+            !matches!(value, Some(value) if value.is_empty()),
+        )
+    }
+
+    pub fn check(mut self, field: &'static str, code: &'static str, valid: bool) -> Self {
+        if !valid {
+            self.0.add(field, ValidationError::new(code));
+        }
+        self
+    }
+
+    pub fn finish(self) -> Result<(), ValidationErrors> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(self.0)
+        }
+    }
+}
+
+impl Default for PartialChecks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Tells a nullable field that was left out of an update (`None`) from one set to `null`
+/// (`Some(None)`), so the latter can clear it.
+///
+/// ```ignore
+/// #[serde(default, deserialize_with = "nullable")]
+/// allergies: Option<Option<String>>,
+/// ```
+pub fn nullable<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Rejects ranges that end before, or when, they start.
+pub fn validate_time_range(
+    start_time: &DateTime<FixedOffset>,
+    end_time: &DateTime<FixedOffset>,
+) -> Result<(), ValidationError> {
+    if end_time > start_time {
+        Ok(())
+    } else {
+        Err(field_error(
+            "endTime",
+            "end_before_start",
+            "endTime must be after startTime",
+        ))
+    }
+}
+
+/// For consents like accepting the code of conduct, which a `bool` alone doesn't enforce.
+#[allow(clippy::trivially_copy_pass_by_ref)] // `validator` passes fields by reference
+pub fn must_be_true(value: &bool) -> Result<(), ValidationError> {
+    if *value {
+        Ok(())
+    } else {
+        Err(ValidationError::new("must_be_true"))
+    }
+}
@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod tests {
+    use api_rust::api_error::{ApiError, FieldError};
+    use axum::http::StatusCode;
+
+    // Test that each error maps to its status code and machine readable code
+    #[test]
+    fn test_status_and_code() {
+        let cases = [
+            (ApiError::BadRequest("bad".to_owned()), 400, "BAD_REQUEST"),
+            (ApiError::Unauthorized, 401, "UNAUTHORIZED"),
+            (ApiError::Forbidden("no".to_owned()), 403, "FORBIDDEN"),
+            (ApiError::NotFound("gone".to_owned()), 404, "NOT_FOUND"),
+            (ApiError::Conflict("taken".to_owned()), 409, "CONFLICT"),
+            (ApiError::Validation(vec![]), 422, "VALIDATION_FAILED"),
+            (ApiError::Internal("boom".to_owned()), 500, "INTERNAL"),
+        ];
+        for (error, status, code) in cases {
+            assert_eq!(error.status_code().as_u16(), status);
+            assert_eq!(error.code(), code);
+        }
+    }
+
+    // Test that client errors keep their message while internal details are hidden
+    #[test]
+    fn test_body_hides_internal_messages() {
+        let body =
+            ApiError::NotFound("Project not found".to_owned()).body(Some("req-1".to_owned()));
+        assert_eq!(body.message, "Project not found");
+        assert_eq!(body.request_id.as_deref(), Some("req-1"));
+
+        let body =
+            ApiError::Internal("connection refused to mysql://root@db".to_owned()).body(None);
+        assert_eq!(body.message, "Internal server error");
+        assert!(!body.message.contains("mysql"));
+
+        let body = ApiError::BadGateway("GCS returned 503".to_owned()).body(None);
+        assert_eq!(body.message, "Upstream service failed");
+    }
+
+    // Test that validation errors carry their field errors and serialize in camelCase
+    #[test]
+    fn test_validation_body() {
+        let field_error = FieldError {
+            field: "firstName".to_owned(),
+            code: "length".to_owned(),
+            message: None,
+        };
+        let body = ApiError::Validation(vec![field_error.clone()]).body(None);
+        assert_eq!(body.field_errors, vec![field_error]);
+
+        let json = serde_json::to_value(&body).unwrap();
+        assert_eq!(json["code"], "VALIDATION_FAILED");
+        assert_eq!(json["fieldErrors"][0]["field"], "firstName");
+        assert!(json.get("requestId").is_some());
+    }
+
+    // Test that errors given as a status, like storage failures, map onto the matching variant
+    #[test]
+    fn test_from_status() {
+        assert_eq!(
+            ApiError::from_status(StatusCode::NOT_FOUND, "File not found".to_owned()).status_code(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            ApiError::from_status(StatusCode::IM_A_TEAPOT, "?".to_owned()).status_code(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+}
@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use api_rust::auth_provider::{token_expiry, AuthIdentity, AuthProvider, LocalJwtAuthProvider};
+    use axum::http::StatusCode;
+    use jsonwebtoken::{encode, get_current_timestamp, EncodingKey, Header};
+    use serde_json::json;
+
+    // Test that a locally issued token authenticates as the user it was issued for
+    #[tokio::test]
+    async fn test_local_token_round_trip() {
+        let provider = LocalJwtAuthProvider::new(b"ci-secret");
+        let token = provider.issue_token("gcp-user", Duration::from_secs(600));
+
+        assert_eq!(
+            provider.authenticate(&token).await.unwrap(),
+            AuthIdentity {
+                uid: "gcp-user".to_owned(),
+                email: None,
+            }
+        );
+    }
+
+    // Test that the email a local token was issued with is passed on for self-registration
+    #[tokio::test]
+    async fn test_local_token_carries_email() {
+        let provider = LocalJwtAuthProvider::new(b"ci-secret");
+        let identity = AuthIdentity {
+            uid: "gcp-user".to_owned(),
+            email: Some("hacker@example.com".to_owned()),
+        };
+        let token = provider.issue_identity_token(&identity, Duration::from_secs(600));
+
+        assert_eq!(provider.authenticate(&token).await.unwrap(), identity);
+    }
+
+    // Test that tokens signed with another secret or garbage are rejected
+    #[tokio::test]
+    async fn test_local_token_with_wrong_secret_is_rejected() {
+        let provider = LocalJwtAuthProvider::new(b"ci-secret");
+        let token = LocalJwtAuthProvider::new(b"other-secret")
+            .issue_token("gcp-user", Duration::from_secs(600));
+
+        assert_eq!(
+            provider.authenticate(&token).await.unwrap_err(),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            provider.authenticate("not-a-token").await.unwrap_err(),
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    // Test that a token past its expiry, beyond the default leeway, is rejected
+    #[tokio::test]
+    async fn test_expired_local_token_is_rejected() {
+        let provider = LocalJwtAuthProvider::new(b"ci-secret");
+        let now = get_current_timestamp();
+        let claims = json!({
+            "sub": "gcp-user",
+            "iss": "api-rust-local",
+            "iat": now - 7200,
+            "exp": now - 3600,
+        });
+        let token = encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(b"ci-secret"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            provider.authenticate(&token).await.unwrap_err(),
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    // Test that a token's expiry can be read to close sockets authenticated with it
+    #[test]
+    fn test_token_expiry() {
+        let provider = LocalJwtAuthProvider::new(b"ci-secret");
+        let now = get_current_timestamp();
+        let token = provider.issue_token("gcp-user", Duration::from_secs(600));
+
+        let expires_at = token_expiry(&token).unwrap();
+        assert!((now + 600..=now + 601).contains(&expires_at));
+        assert_eq!(token_expiry("not-a-token"), None);
+    }
+}
@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, time::Duration};
+
+    use api_rust::firebase_auth::{parse_max_age, FirebaseTokenVerifier};
+    use axum::http::StatusCode;
+    use jsonwebtoken::{
+        encode, get_current_timestamp, Algorithm, DecodingKey, EncodingKey, Header,
+    };
+    use serde_json::json;
+
+    const PROJECT_ID: &str = "hackathon-test";
+    const KEY_ID: &str = "test-key";
+
+    fn verifier() -> FirebaseTokenVerifier {
+        let key = DecodingKey::from_rsa_pem(include_bytes!("fixtures/firebase_test_key.pub.pem"))
+            .unwrap();
+        FirebaseTokenVerifier::with_keys(
+            PROJECT_ID.to_owned(),
+            HashMap::from([(KEY_ID.to_owned(), key)]),
+        )
+    }
+
+    fn sign(key_id: &str, claims: &serde_json::Value) -> String {
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = Some(key_id.to_owned());
+        let key =
+            EncodingKey::from_rsa_pem(include_bytes!("fixtures/firebase_test_key.pem")).unwrap();
+        encode(&header, claims, &key).unwrap()
+    }
+
+    fn claims() -> serde_json::Value {
+        let now = get_current_timestamp();
+        json!({
+            "iss": format!("https://securetoken.google.com/{PROJECT_ID}"),
+            "aud": PROJECT_ID,
+            "sub": "firebase-uid",
+            "auth_time": now - 60,
+            "iat": now - 60,
+            "exp": now + 3600,
+            "email": "hacker@example.com",
+        })
+    }
+
+    // Test that a well formed token yields its subject
+    #[tokio::test]
+    async fn test_valid_token_is_accepted() {
+        let token = sign(KEY_ID, &claims());
+
+        let verified = verifier().verify(&token).await.unwrap();
+        assert_eq!(verified.sub, "firebase-uid");
+        assert_eq!(verified.email.as_deref(), Some("hacker@example.com"));
+    }
+
+    // Test that tokens for another project, from another issuer or past expiry are rejected
+    #[tokio::test]
+    async fn test_wrong_claims_are_rejected() {
+        let verifier = verifier();
+        let mut wrong_audience = claims();
+        wrong_audience["aud"] = json!("another-project");
+        let mut wrong_issuer = claims();
+        wrong_issuer["iss"] = json!("https://securetoken.google.com/another-project");
+        let mut expired = claims();
+        expired["exp"] = json!(get_current_timestamp() - 3600);
+        let mut empty_subject = claims();
+        empty_subject["sub"] = json!("");
+
+        for claims in [wrong_audience, wrong_issuer, expired, empty_subject] {
+            let token = sign(KEY_ID, &claims);
+            assert_eq!(
+                verifier.verify(&token).await.unwrap_err(),
+                StatusCode::UNAUTHORIZED
+            );
+        }
+    }
+
+    // Test that a token signed with an unknown key id or tampered with is rejected
+    #[tokio::test]
+    async fn test_unknown_key_and_bad_signature_are_rejected() {
+        let verifier = verifier();
+
+        let unknown_key = sign("rotated-away", &claims());
+        assert_eq!(
+            verifier.verify(&unknown_key).await.unwrap_err(),
+            StatusCode::UNAUTHORIZED
+        );
+
+        let token = sign(KEY_ID, &claims());
+        let (rest, signature) = token.rsplit_once('.').unwrap();
+        let tampered = format!("{rest}.{}", signature.chars().rev().collect::<String>());
+        assert_eq!(
+            verifier.verify(&tampered).await.unwrap_err(),
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    // Test that the key cache lifetime is read from Google's Cache-Control header
+    #[test]
+    fn test_parse_max_age() {
+        assert_eq!(
+            parse_max_age("public, max-age=19723, must-revalidate, no-transform"),
+            Some(Duration::from_secs(19723))
+        );
+        assert_eq!(parse_max_age("no-cache"), None);
+    }
+}
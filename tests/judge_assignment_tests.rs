@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use api_rust::judge_assignment::{plan_assignments, JudgeAssignment, ProjectToJudge};
+
+    fn project(id: &str, members: &[&str]) -> ProjectToJudge {
+        ProjectToJudge {
+            project_id: id.to_owned(),
+            member_ids: members.iter().map(|member| (*member).to_owned()).collect(),
+        }
+    }
+
+    fn judges(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| (*id).to_owned()).collect()
+    }
+
+    // Test that only users off the team may judge a project, as checked for manual assignments
+    #[test]
+    fn test_can_be_judged_by() {
+        let project = project("a", &["hacker"]);
+
+        assert!(!project.can_be_judged_by("hacker"));
+        assert!(project.can_be_judged_by("judge"));
+    }
+
+    // Test that every project receives distinct judges and load is balanced
+    #[test]
+    fn test_plan_is_balanced_and_distinct() {
+        let projects = vec![project("a", &[]), project("b", &[]), project("c", &[])];
+        let plan = plan_assignments(&projects, &judges(&["j1", "j2", "j3"]), &[], 2, 2);
+
+        assert!(plan.unfilled_projects.is_empty());
+        assert_eq!(plan.assignments.len(), 6);
+
+        let mut per_project: HashMap<&str, HashSet<&str>> = HashMap::new();
+        let mut load: HashMap<&str, usize> = HashMap::new();
+        for assignment in &plan.assignments {
+            assert!(per_project
+                .entry(assignment.project_id.as_str())
+                .or_default()
+                .insert(assignment.judge_id.as_str()));
+            *load.entry(assignment.judge_id.as_str()).or_default() += 1;
+        }
+        assert!(load.values().all(|count| *count == 2));
+    }
+
+    // Test that judges are never assigned to their own team's project
+    #[test]
+    fn test_plan_skips_own_project() {
+        let projects = vec![project("a", &["j1"]), project("b", &["j2"])];
+        let plan = plan_assignments(&projects, &judges(&["j1", "j2"]), &[], 1, 5);
+
+        assert!(plan.assignments.contains(&JudgeAssignment {
+            project_id: "a".to_owned(),
+            judge_id: "j2".to_owned(),
+        }));
+        assert!(plan.assignments.contains(&JudgeAssignment {
+            project_id: "b".to_owned(),
+            judge_id: "j1".to_owned(),
+        }));
+    }
+
+    // Test that the load cap and existing assignments are respected
+    #[test]
+    fn test_plan_respects_cap_and_existing() {
+        let projects = vec![project("a", &[]), project("b", &[])];
+        let existing = vec![JudgeAssignment {
+            project_id: "a".to_owned(),
+            judge_id: "j1".to_owned(),
+        }];
+        let plan = plan_assignments(&projects, &judges(&["j1"]), &existing, 2, 1);
+
+        assert!(plan.assignments.is_empty());
+        assert_eq!(plan.unfilled_projects.len(), 2);
+        let missing: usize = plan
+            .unfilled_projects
+            .iter()
+            .map(|project| project.missing_judges)
+            .sum();
+        assert_eq!(missing, 3);
+    }
+
+    // Test that a huge load cap plans the same as a sufficient one instead of looping up to it
+    #[test]
+    fn test_plan_with_huge_cap() {
+        let projects = vec![project("a", &[]), project("b", &["j1"])];
+        let judge_ids = judges(&["j1", "j2"]);
+
+        let plan = plan_assignments(&projects, &judge_ids, &[], 1, usize::MAX);
+        assert!(plan.unfilled_projects.is_empty());
+        assert_eq!(
+            plan.assignments,
+            plan_assignments(&projects, &judge_ids, &[], 1, 2).assignments
+        );
+    }
+}
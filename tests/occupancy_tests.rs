@@ -0,0 +1,56 @@
+#[cfg(test)]
+mod tests {
+    use api_rust::{
+        occupancy::{count_occupancy, EventOccupancy},
+        prisma::ScanType,
+    };
+
+    fn occupancy(capacity: Option<i32>, occupancy: i64) -> EventOccupancy {
+        EventOccupancy {
+            event_id: "event".to_owned(),
+            location_id: "location".to_owned(),
+            hackathon_id: "hackathon".to_owned(),
+            capacity,
+            occupancy,
+        }
+    }
+
+    // Test that checked out hackers are no longer counted
+    #[test]
+    fn test_count_occupancy() {
+        let scans = [
+            ("alice", ScanType::CheckIn),
+            ("bob", ScanType::CheckIn),
+            ("alice", ScanType::CheckOut),
+        ];
+        assert_eq!(count_occupancy(scans), 1);
+        assert_eq!(count_occupancy([]), 0);
+    }
+
+    // Test that hackers who come back after checking out are counted again, once
+    #[test]
+    fn test_count_reentry() {
+        let scans = [
+            ("alice", ScanType::CheckIn),
+            ("alice", ScanType::CheckOut),
+            ("alice", ScanType::CheckIn),
+            ("alice", ScanType::CheckOut),
+            ("alice", ScanType::CheckIn),
+        ];
+        assert_eq!(count_occupancy(scans), 1);
+    }
+
+    // Test that an event is full once occupancy reaches its capacity
+    #[test]
+    fn test_is_full() {
+        assert!(!occupancy(Some(30), 29).is_full());
+        assert!(occupancy(Some(30), 30).is_full());
+        assert!(occupancy(Some(30), 31).is_full());
+    }
+
+    // Test that events without a capacity are never full
+    #[test]
+    fn test_unlimited_is_never_full() {
+        assert!(!occupancy(None, 1000).is_full());
+    }
+}
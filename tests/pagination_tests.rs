@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod tests {
+    use api_rust::pagination::{PageParams, SortOrder, DEFAULT_LIMIT};
+    use prisma_client_rust::Direction;
+    use validator::Validate;
+
+    // Test that the limit defaults and is kept within bounds
+    #[test]
+    fn test_limit() {
+        assert_eq!(PageParams::default().limit(), DEFAULT_LIMIT);
+        let params = PageParams {
+            limit: Some(10),
+            ..Default::default()
+        };
+        assert_eq!(params.limit(), 10);
+        assert_eq!(params.take(), 11);
+    }
+
+    // Test that offsets are skipped, and the cursor's own row is skipped when given
+    #[test]
+    fn test_skip() {
+        assert_eq!(PageParams::default().skip(), 0);
+        let params = PageParams {
+            offset: Some(50),
+            ..Default::default()
+        };
+        assert_eq!(params.skip(), 50);
+        let params = PageParams {
+            cursor: Some("abc".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(params.skip(), 1);
+    }
+
+    // Test that the endpoint's default order is used unless one is given
+    #[test]
+    fn test_direction() {
+        assert!(matches!(
+            PageParams::default().direction(SortOrder::Desc),
+            Direction::Desc
+        ));
+        let params = PageParams {
+            order: Some(SortOrder::Asc),
+            ..Default::default()
+        };
+        assert!(matches!(params.direction(SortOrder::Desc), Direction::Asc));
+    }
+
+    // Test that the extra row is dropped and becomes the next cursor
+    #[test]
+    fn test_page_with_more_items() {
+        let params = PageParams {
+            limit: Some(2),
+            ..Default::default()
+        };
+        let page = params.page(vec![1, 2, 3], 7, ToString::to_string);
+        assert_eq!(page.items, vec![1, 2]);
+        assert_eq!(page.total, 7);
+        assert_eq!(page.next_cursor.as_deref(), Some("2"));
+    }
+
+    // Test that the last page has no next cursor
+    #[test]
+    fn test_last_page() {
+        let params = PageParams {
+            limit: Some(2),
+            ..Default::default()
+        };
+        let page = params.page(vec![1, 2], 2, ToString::to_string);
+        assert_eq!(page.items, vec![1, 2]);
+        assert_eq!(page.next_cursor, None);
+    }
+
+    // Test that out of range limits and combining cursor with offset are rejected
+    #[test]
+    fn test_validation() {
+        assert!(PageParams::default().validate().is_ok());
+        for limit in [0, 101] {
+            let params = PageParams {
+                limit: Some(limit),
+                ..Default::default()
+            };
+            assert!(params.validate().is_err());
+        }
+        let params = PageParams {
+            offset: Some(10),
+            cursor: Some("abc".to_owned()),
+            ..Default::default()
+        };
+        assert!(params.validate().is_err());
+    }
+}
@@ -0,0 +1,119 @@
+#[cfg(test)]
+mod tests {
+    use api_rust::{
+        permissions::{Permission, PermissionTable, RoleGrant},
+        prisma::Role,
+    };
+
+    // Test that the default table grants hackathon writes to Exec and Tech but not Finance
+    #[test]
+    fn test_default_table_hackathon_write() {
+        let table = PermissionTable::default();
+
+        assert!(table.allows(Role::Exec, Permission::HackathonWrite));
+        assert!(table.allows(Role::Tech, Permission::HackathonWrite));
+        assert!(!table.allows(Role::Finance, Permission::HackathonWrite));
+        assert!(!table.allows(Role::Team, Permission::HackathonWrite));
+    }
+
+    // Test that reimbursements can only be approved by Finance and NONE holds nothing
+    #[test]
+    fn test_default_table_reimbursements_and_none() {
+        let table = PermissionTable::default();
+
+        assert_eq!(
+            table.roles_with(Permission::ReimbursementsApprove),
+            vec![Role::Finance]
+        );
+        assert!(Permission::ALL
+            .iter()
+            .all(|permission| !table.allows(Role::None, *permission)));
+    }
+
+    // Test that every organizer role can judge by default
+    #[test]
+    fn test_default_judges() {
+        assert_eq!(
+            PermissionTable::default().roles_with(Permission::ScoresWrite),
+            vec![
+                Role::Volunteer,
+                Role::Team,
+                Role::Exec,
+                Role::Tech,
+                Role::Finance
+            ]
+        );
+    }
+
+    // Test that resumes can be read by sponsors and organizers from Team up, and sponsors hold
+    // nothing else
+    #[test]
+    fn test_default_resume_readers() {
+        let table = PermissionTable::default();
+
+        assert_eq!(
+            table.roles_with(Permission::ResumesRead),
+            vec![
+                Role::Sponsor,
+                Role::Team,
+                Role::Exec,
+                Role::Tech,
+                Role::Finance
+            ]
+        );
+        assert_eq!(table.permissions(Role::Sponsor).len(), 1);
+        assert!(table.covers(Role::Team, Role::Sponsor));
+        assert!(!table.covers(Role::Volunteer, Role::Sponsor));
+    }
+
+    // Test that a role only covers roles whose permissions are a subset of its own
+    #[test]
+    fn test_covers() {
+        let table = PermissionTable::default();
+
+        assert!(table.covers(Role::Exec, Role::Team));
+        assert!(table.covers(Role::Exec, Role::Tech));
+        assert!(table.covers(Role::Finance, Role::Team));
+        assert!(table.covers(Role::Team, Role::None));
+        assert!(!table.covers(Role::Finance, Role::Exec));
+        assert!(!table.covers(Role::Exec, Role::Finance));
+        assert!(!table.covers(Role::Volunteer, Role::Team));
+    }
+
+    // Test that a table is read from the JSON format of PERMISSIONS_FILE
+    #[test]
+    fn test_table_from_json() {
+        let grants: Vec<RoleGrant> = serde_json::from_str(
+            r#"[
+                { "role": "VOLUNTEER", "permissions": ["scores:write", "project:read"] },
+                { "role": "FINANCE", "permissions": ["reimbursements:approve", "hackathon:write"] }
+            ]"#,
+        )
+        .unwrap();
+        let table = PermissionTable::new(grants);
+
+        assert!(table.allows(Role::Finance, Permission::HackathonWrite));
+        assert!(table.allows(Role::Volunteer, Permission::ScoresWrite));
+        assert!(!table.allows(Role::Exec, Permission::HackathonWrite));
+    }
+
+    // Test that unknown permission names are rejected rather than ignored
+    #[test]
+    fn test_unknown_permission_is_rejected() {
+        let grants: Result<Vec<RoleGrant>, _> =
+            serde_json::from_str(r#"[{ "role": "EXEC", "permissions": ["hackathon:destroy"] }]"#);
+
+        assert!(grants.is_err());
+    }
+
+    // Test that permission names match their serialized form
+    #[test]
+    fn test_permission_names() {
+        for permission in Permission::ALL {
+            assert_eq!(
+                serde_json::to_value(permission).unwrap(),
+                serde_json::Value::String(permission.name().to_owned())
+            );
+        }
+    }
+}
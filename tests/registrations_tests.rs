@@ -0,0 +1,142 @@
+#[cfg(test)]
+mod tests {
+    use api_rust::{
+        permissions::{Permission, PermissionTable},
+        prisma::{
+            _prisma::read_filters::{BoolFilter, DateTimeFilter, StringFilter},
+            registration::WhereParam,
+            Role,
+        },
+        routes::registrations::{can_access, ensure_withdrawable, RegistrationFilterParams},
+    };
+    use chrono::DateTime;
+    use serde_json::{json, Value};
+
+    fn filters(params: Value) -> Vec<WhereParam> {
+        serde_json::from_value::<RegistrationFilterParams>(params)
+            .unwrap()
+            .into_filters("hackathon".to_owned())
+    }
+
+    // Test that a listing without filters is still scoped to the hackathon
+    #[test]
+    fn test_filters_scoped_to_hackathon() {
+        let filters = filters(json!({}));
+
+        assert_eq!(filters.len(), 1);
+        assert!(matches!(
+            &filters[0],
+            WhereParam::HackathonId(StringFilter::Equals(id)) if id == "hackathon"
+        ));
+    }
+
+    // Test that the hackathon in the params is left to the handler to resolve
+    #[test]
+    fn test_filters_ignore_requested_hackathon() {
+        let filters = filters(json!({ "hackathonId": "other" }));
+
+        assert_eq!(filters.len(), 1);
+        assert!(matches!(
+            &filters[0],
+            WhereParam::HackathonId(StringFilter::Equals(id)) if id == "hackathon"
+        ));
+    }
+
+    // Test that each given param adds its filter, with `from` inclusive and `to` exclusive
+    #[test]
+    fn test_filters_from_params() {
+        let from = DateTime::parse_from_rfc3339("2024-03-01T00:00:00+00:00").unwrap();
+        let to = DateTime::parse_from_rfc3339("2024-03-08T00:00:00+00:00").unwrap();
+        let filters = filters(json!({
+            "university": "Penn State",
+            "travelReimbursement": false,
+            "from": "2024-03-01T00:00:00+00:00",
+            "to": "2024-03-08T00:00:00+00:00",
+        }));
+
+        assert_eq!(filters.len(), 5);
+        assert!(matches!(&filters[0], WhereParam::HackathonId(_)));
+        assert!(matches!(
+            &filters[1],
+            WhereParam::University(StringFilter::Equals(university)) if university == "Penn State"
+        ));
+        assert!(matches!(
+            &filters[2],
+            WhereParam::TravelReimbursement(BoolFilter::Equals(false))
+        ));
+        assert!(matches!(
+            &filters[3],
+            WhereParam::Time(DateTimeFilter::Gte(time)) if *time == from
+        ));
+        assert!(matches!(
+            &filters[4],
+            WhereParam::Time(DateTimeFilter::Lt(time)) if *time == to
+        ));
+    }
+
+    // Test that owners can always act on their registration, whatever their role
+    #[test]
+    fn test_owner_can_access() {
+        let table = PermissionTable::default();
+
+        for permission in [
+            Permission::RegistrationRead,
+            Permission::RegistrationWrite,
+            Permission::ResumesRead,
+        ] {
+            assert!(can_access(&table, "user", "user", Role::None, permission));
+        }
+    }
+
+    // Test that anyone else needs the permission for what they're doing
+    #[test]
+    fn test_others_need_permission() {
+        let table = PermissionTable::default();
+
+        assert!(!can_access(
+            &table,
+            "owner",
+            "user",
+            Role::None,
+            Permission::RegistrationRead
+        ));
+        assert!(!can_access(
+            &table,
+            "owner",
+            "user",
+            Role::Volunteer,
+            Permission::RegistrationWrite
+        ));
+        assert!(can_access(
+            &table,
+            "owner",
+            "user",
+            Role::Team,
+            Permission::RegistrationWrite
+        ));
+        assert!(can_access(
+            &table,
+            "owner",
+            "user",
+            Role::Sponsor,
+            Permission::ResumesRead
+        ));
+        assert!(!can_access(
+            &table,
+            "owner",
+            "user",
+            Role::Sponsor,
+            Permission::RegistrationRead
+        ));
+    }
+
+    // Test that only registrations that were never scanned can be withdrawn
+    #[test]
+    fn test_ensure_withdrawable() {
+        assert!(ensure_withdrawable(0).is_ok());
+        assert_eq!(
+            ensure_withdrawable(2).unwrap_err().status_code().as_u16(),
+            409
+        );
+    }
+}
@@ -0,0 +1,69 @@
+#[cfg(test)]
+mod tests {
+    use api_rust::routes::scans::{bucket_scans, ScanBucket};
+    use chrono::{DateTime, Duration, Utc};
+
+    fn at(time: &str) -> Option<DateTime<Utc>> {
+        Some(
+            DateTime::parse_from_rfc3339(time)
+                .unwrap()
+                .with_timezone(&Utc),
+        )
+    }
+
+    // Test that scans are counted per window, with windows starting on the hour
+    #[test]
+    fn test_buckets() {
+        let times = [
+            at("2024-03-09T10:05:00Z"),
+            at("2024-03-09T10:59:59Z"),
+            at("2024-03-09T11:00:00Z"),
+        ];
+        assert_eq!(
+            bucket_scans(times, Duration::hours(1)).buckets,
+            vec![
+                ScanBucket {
+                    start: at("2024-03-09T10:00:00Z").unwrap(),
+                    count: 2
+                },
+                ScanBucket {
+                    start: at("2024-03-09T11:00:00Z").unwrap(),
+                    count: 1
+                },
+            ]
+        );
+    }
+
+    // Test that empty windows between scans are kept so the timeline has no gaps
+    #[test]
+    fn test_empty_buckets() {
+        let times = [at("2024-03-09T10:20:00Z"), at("2024-03-09T10:01:00Z")];
+        let buckets = bucket_scans(times, Duration::minutes(5)).buckets;
+        assert_eq!(buckets.len(), 4);
+        assert_eq!(Some(buckets[0].start), at("2024-03-09T10:00:00Z"));
+        assert_eq!(
+            buckets
+                .iter()
+                .map(|bucket| bucket.count)
+                .collect::<Vec<_>>(),
+            vec![1, 0, 0, 1]
+        );
+    }
+
+    // Test that no scans make no buckets
+    #[test]
+    fn test_no_scans() {
+        let timeline = bucket_scans([], Duration::hours(1));
+        assert!(timeline.buckets.is_empty());
+        assert_eq!(timeline.undated, 0);
+    }
+
+    // Test that scans without a time are counted apart instead of landing in a bucket
+    #[test]
+    fn test_undated_scans() {
+        let timeline = bucket_scans([None, at("2024-03-09T10:05:00Z"), None], Duration::hours(1));
+        assert_eq!(timeline.undated, 2);
+        assert_eq!(timeline.buckets.len(), 1);
+        assert_eq!(timeline.buckets[0].count, 1);
+    }
+}
@@ -0,0 +1,146 @@
+#[cfg(test)]
+mod tests {
+    use api_rust::scoring::{build_leaderboard, AggregationMode, ProjectScores, ScoreCard};
+
+    fn card(judge_id: &str, categories: [f64; 5], challenges: [Option<f64>; 3]) -> ScoreCard {
+        ScoreCard {
+            judge_id: judge_id.to_owned(),
+            categories,
+            challenges,
+        }
+    }
+
+    fn project(id: &str, cards: Vec<ScoreCard>) -> ProjectScores {
+        ProjectScores {
+            project_id: id.to_owned(),
+            project_name: format!("Project {id}"),
+            cards,
+        }
+    }
+
+    fn order(projects: Vec<ProjectScores>, mode: AggregationMode) -> Vec<String> {
+        build_leaderboard(projects, mode)
+            .overall
+            .into_iter()
+            .map(|entry| entry.project_id)
+            .collect()
+    }
+
+    // Test that category averages are taken across judges
+    #[test]
+    fn test_leaderboard_averages_categories() {
+        let leaderboard = build_leaderboard(
+            vec![project(
+                "a",
+                vec![
+                    card("judge-1", [5.0, 4.0, 3.0, 2.0, 1.0], [None; 3]),
+                    card("judge-2", [3.0, 4.0, 5.0, 2.0, 1.0], [None; 3]),
+                ],
+            )],
+            AggregationMode::Raw,
+        );
+
+        let entry = &leaderboard.overall[0];
+        assert_eq!(entry.judge_count, 2);
+        assert_eq!(entry.creativity, 4.0);
+        assert_eq!(entry.implementation, 4.0);
+        assert_eq!(entry.total, 3.0);
+    }
+
+    // Test that projects are ordered by total and unscored projects sink to the bottom
+    #[test]
+    fn test_leaderboard_orders_by_total() {
+        let projects = vec![
+            project("unscored", vec![]),
+            project("low", vec![card("judge-1", [1.0; 5], [None; 3])]),
+            project("high", vec![card("judge-1", [5.0; 5], [None; 3])]),
+        ];
+
+        assert_eq!(
+            order(projects, AggregationMode::Raw),
+            vec!["high", "low", "unscored"]
+        );
+    }
+
+    // A harsh judge's favourite should beat a lenient judge's least favourite
+    fn harsh_and_lenient() -> Vec<ProjectScores> {
+        vec![
+            project("harsh-favourite", vec![card("harsh", [3.0; 5], [None; 3])]),
+            project("harsh-other", vec![card("harsh", [1.0; 5], [None; 3])]),
+            project(
+                "lenient-favourite",
+                vec![card("lenient", [5.0; 5], [None; 3])],
+            ),
+            project("lenient-other", vec![card("lenient", [4.0; 5], [None; 3])]),
+        ]
+    }
+
+    // Test that raw averaging favours whoever drew the lenient judge
+    #[test]
+    fn test_raw_mode_punishes_harsh_judge() {
+        let order = order(harsh_and_lenient(), AggregationMode::Raw);
+        assert_eq!(order[1], "lenient-other");
+        assert_eq!(order[2], "harsh-favourite");
+    }
+
+    // Test that z-score and Borda modes put each judge's favourite on top
+    #[test]
+    fn test_normalized_modes_correct_for_harsh_judge() {
+        for mode in [AggregationMode::ZScore, AggregationMode::Borda] {
+            let order = order(harsh_and_lenient(), mode);
+            let top_two: Vec<&str> = order[..2].iter().map(String::as_str).collect();
+            assert!(top_two.contains(&"harsh-favourite"), "{mode:?}");
+            assert!(top_two.contains(&"lenient-favourite"), "{mode:?}");
+        }
+    }
+
+    // Test that challenge rankings only include projects scored for that challenge
+    #[test]
+    fn test_challenge_rankings() {
+        let leaderboard = build_leaderboard(
+            vec![
+                project(
+                    "a",
+                    vec![card("judge-1", [3.0; 5], [Some(2.0), None, None])],
+                ),
+                project(
+                    "b",
+                    vec![card("judge-1", [3.0; 5], [Some(4.0), None, None])],
+                ),
+            ],
+            AggregationMode::Raw,
+        );
+
+        let first: Vec<&str> = leaderboard.challenges[0]
+            .entries
+            .iter()
+            .map(|entry| entry.project_id.as_str())
+            .collect();
+        assert_eq!(first, vec!["b", "a"]);
+        assert!(leaderboard.challenges[1].entries.is_empty());
+        assert_eq!(leaderboard.challenges[2].challenge, 3);
+    }
+
+    // Test that a challenge marked 0 is ranked rather than treated as not entered
+    #[test]
+    fn test_zero_challenge_score() {
+        let leaderboard = build_leaderboard(
+            vec![
+                project(
+                    "zero",
+                    vec![card("judge-1", [3.0; 5], [Some(0.0), None, None])],
+                ),
+                project(
+                    "two",
+                    vec![card("judge-1", [3.0; 5], [Some(2.0), None, None])],
+                ),
+            ],
+            AggregationMode::Borda,
+        );
+
+        let entries = &leaderboard.challenges[0].entries;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].project_id, "zero");
+        assert_eq!(entries[1].score, 0.0);
+    }
+}
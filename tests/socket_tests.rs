@@ -0,0 +1,127 @@
+#[cfg(test)]
+mod tests {
+    use api_rust::{
+        permissions::PermissionTable,
+        prisma::{Role, ScanType},
+        socket::{EventDeletedPayload, HackathonPayload, Rooms, ScanCreatedPayload, SocketEvent},
+    };
+    use chrono::DateTime;
+    use serde_json::json;
+
+    fn scan_created() -> SocketEvent {
+        SocketEvent::ScanCreated(ScanCreatedPayload {
+            id: "scan".to_owned(),
+            event_id: "event".to_owned(),
+            registration_id: "registration".to_owned(),
+            organizer_id: "organizer".to_owned(),
+            hackathon_id: "hackathon".to_owned(),
+            r#type: ScanType::CheckIn,
+            created_at: Some(DateTime::parse_from_rfc3339("2024-03-09T10:00:00+00:00").unwrap()),
+            device_id: None,
+        })
+    }
+
+    fn event_deleted() -> SocketEvent {
+        SocketEvent::EventDeleted(EventDeletedPayload {
+            id: "event".to_owned(),
+            hackathon_id: "hackathon".to_owned(),
+        })
+    }
+
+    // Test that scans only reach organizers while schedule changes reach everyone
+    #[test]
+    fn test_rooms() {
+        assert_eq!(scan_created().rooms(), &[Rooms::ADMIN, Rooms::EXEC]);
+        assert_eq!(
+            event_deleted().rooms(),
+            &[Rooms::MOBILE, Rooms::ADMIN, Rooms::EXEC]
+        );
+    }
+
+    // Test that events are emitted under their catalogue names
+    #[test]
+    fn test_names() {
+        assert_eq!(scan_created().name(), "scan:created");
+        assert_eq!(event_deleted().name(), "event:deleted");
+    }
+
+    // Test that the payload is sent on its own, without the variant name
+    #[test]
+    fn test_payload() {
+        assert_eq!(
+            serde_json::to_value(event_deleted()).unwrap(),
+            json!({ "id": "event", "hackathonId": "hackathon" })
+        );
+        assert_eq!(
+            serde_json::to_value(scan_created()).unwrap(),
+            json!({
+                "id": "scan",
+                "eventId": "event",
+                "registrationId": "registration",
+                "organizerId": "organizer",
+                "hackathonId": "hackathon",
+                "type": "CHECK_IN",
+                "createdAt": "2024-03-09T10:00:00+00:00",
+            })
+        );
+    }
+
+    // Test that the active hackathon is sent with the fields its schema documents
+    #[test]
+    fn test_hackathon_payload() {
+        let payload = SocketEvent::ActiveHackathonChanged(HackathonPayload {
+            id: "hackathon".to_owned(),
+            name: "HackPSU".to_owned(),
+            start_time: DateTime::parse_from_rfc3339("2024-03-09T10:00:00+00:00").unwrap(),
+            end_time: DateTime::parse_from_rfc3339("2024-03-10T10:00:00+00:00").unwrap(),
+            active: true,
+        });
+        assert_eq!(payload.name(), "hackathon:activeChanged");
+        assert_eq!(
+            serde_json::to_value(payload).unwrap(),
+            json!({
+                "id": "hackathon",
+                "name": "HackPSU",
+                "startTime": "2024-03-09T10:00:00+00:00",
+                "endTime": "2024-03-10T10:00:00+00:00",
+                "active": true,
+            })
+        );
+    }
+
+    // Test that connections are joined to the rooms their role allows
+    #[test]
+    fn test_rooms_for_role() {
+        let table = PermissionTable::default();
+        assert_eq!(Rooms::for_role(&table, Role::None), vec![Rooms::MOBILE]);
+        assert_eq!(
+            Rooms::for_role(&table, Role::Team),
+            vec![Rooms::MOBILE, Rooms::ADMIN]
+        );
+        assert_eq!(
+            Rooms::for_role(&table, Role::Exec),
+            vec![Rooms::MOBILE, Rooms::ADMIN, Rooms::EXEC]
+        );
+    }
+
+    // Test that joins are rejected for rooms the role can't join or that don't exist
+    #[test]
+    fn test_check_join() {
+        let table = PermissionTable::default();
+        assert!(Rooms::check_join(&table, Role::Volunteer, Rooms::MOBILE).is_ok());
+        assert_eq!(
+            Rooms::check_join(&table, Role::Volunteer, Rooms::ADMIN)
+                .unwrap_err()
+                .status_code()
+                .as_u16(),
+            403
+        );
+        assert_eq!(
+            Rooms::check_join(&table, Role::Exec, "lobby")
+                .unwrap_err()
+                .status_code()
+                .as_u16(),
+            404
+        );
+    }
+}
@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod tests {
+    use api_rust::storage_bucket_service::{LocalStorageBackend, StorageBackend};
+    use axum::http::StatusCode;
+
+    fn temp_backend() -> LocalStorageBackend {
+        let root = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        LocalStorageBackend::new(root)
+    }
+
+    // Test that an uploaded object can be read back
+    #[tokio::test]
+    async fn test_local_upload_then_download() {
+        let backend = temp_backend();
+        backend
+            .upload(
+                "resumes",
+                "hackathon/registration.pdf",
+                b"%PDF-1.4".to_vec(),
+            )
+            .await
+            .unwrap();
+
+        let file = backend
+            .download("resumes", "hackathon/registration.pdf")
+            .await
+            .unwrap();
+        assert_eq!(file.as_ref(), b"%PDF-1.4");
+    }
+
+    // Test that a missing object maps to 404
+    #[tokio::test]
+    async fn test_local_download_missing_file() {
+        let backend = temp_backend();
+        let result = backend.download("resumes", "missing.pdf").await;
+        assert_eq!(result.unwrap_err().1, StatusCode::NOT_FOUND);
+    }
+
+    // Test that object names cannot escape the storage root
+    #[tokio::test]
+    async fn test_local_rejects_path_traversal() {
+        let backend = temp_backend();
+        let result = backend
+            .upload("resumes", "../outside.pdf", b"%PDF-1.4".to_vec())
+            .await;
+        assert_eq!(result.unwrap_err().1, StatusCode::BAD_REQUEST);
+    }
+}
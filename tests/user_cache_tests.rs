@@ -0,0 +1,68 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use api_rust::user_cache::TokenCache;
+
+    // Test that a cached value is returned for the same token and lookups are counted
+    #[test]
+    fn test_hit_and_miss_are_counted() {
+        let cache = TokenCache::new(Duration::from_secs(60));
+        assert_eq!(cache.get("token"), None);
+
+        cache.insert("token", "user-1".to_owned(), u64::MAX);
+        assert_eq!(cache.get("token").as_deref(), Some("user-1"));
+        assert_eq!(cache.get("other-token"), None);
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 2);
+        assert_eq!(stats.entries, 1);
+        assert!((stats.hit_rate - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    // Test that entries stop being returned once the TTL has passed
+    #[test]
+    fn test_entries_expire() {
+        let cache = TokenCache::new(Duration::from_millis(10));
+        cache.insert("token", "user-1".to_owned(), u64::MAX);
+        std::thread::sleep(Duration::from_millis(20));
+
+        assert_eq!(cache.get("token"), None);
+        assert_eq!(cache.stats().entries, 0);
+    }
+
+    // Test that an entry is never returned past its token's expiry, even within the TTL
+    #[test]
+    fn test_expired_token_is_not_cached() {
+        let cache = TokenCache::new(Duration::from_secs(60));
+        cache.insert("token", "user-1".to_owned(), 1);
+
+        assert_eq!(cache.get("token"), None);
+        assert_eq!(cache.stats().entries, 0);
+    }
+
+    // Test that invalidation drops every token of the matching value and nothing else
+    #[test]
+    fn test_invalidate_where() {
+        let cache = TokenCache::new(Duration::from_secs(60));
+        cache.insert("laptop", "user-1".to_owned(), u64::MAX);
+        cache.insert("phone", "user-1".to_owned(), u64::MAX);
+        cache.insert("other", "user-2".to_owned(), u64::MAX);
+
+        cache.invalidate_where(|user| user == "user-1");
+
+        assert_eq!(cache.get("laptop"), None);
+        assert_eq!(cache.get("phone"), None);
+        assert_eq!(cache.get("other").as_deref(), Some("user-2"));
+    }
+
+    // Test that a zero TTL disables caching
+    #[test]
+    fn test_zero_ttl_disables_cache() {
+        let cache = TokenCache::new(Duration::ZERO);
+        cache.insert("token", "user-1".to_owned(), u64::MAX);
+
+        assert_eq!(cache.get("token"), None);
+    }
+}
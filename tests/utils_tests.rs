@@ -6,8 +6,11 @@ mod tests {
     #[tokio::test]
     async fn test_handle_404() {
         let response = handle_404().await;
-        assert_eq!(response.0, StatusCode::NOT_FOUND);
-        assert_eq!(response.1, "We couldn't find the resource you requested.");
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            response.body(None).message,
+            "We couldn't find the resource you requested."
+        );
     }
 
     use api_rust::utils::hello_world;
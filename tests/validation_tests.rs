@@ -0,0 +1,183 @@
+#[cfg(test)]
+mod tests {
+    use api_rust::{
+        api_error::{ApiError, FieldError},
+        utils::PHONE_VALIDATOR,
+        validation::{
+            field_error, field_errors, invalid_field, must_be_true, nullable, validate_time_range,
+        },
+    };
+    use chrono::DateTime;
+    use validator::{Validate, ValidationError};
+
+    #[derive(Validate)]
+    struct Address {
+        #[validate(length(min = 1))]
+        postal_code: String,
+    }
+
+    #[derive(Validate)]
+    #[validate(schema(function = "validate_window"))]
+    struct Entity {
+        #[validate(length(min = 1))]
+        first_name: String,
+        #[validate(range(min = 0, max = 5))]
+        challenge_1: i32,
+        #[validate]
+        home_address: Address,
+        #[validate]
+        past_addresses: Vec<Address>,
+        start: i32,
+        end: i32,
+    }
+
+    fn validate_window(entity: &Entity) -> Result<(), ValidationError> {
+        if entity.end > entity.start {
+            Ok(())
+        } else {
+            Err(field_error(
+                "endTime",
+                "end_before_start",
+                "endTime must be after startTime",
+            ))
+        }
+    }
+
+    fn valid_entity() -> Entity {
+        Entity {
+            first_name: "Ada".to_owned(),
+            challenge_1: 3,
+            home_address: Address {
+                postal_code: "16802".to_owned(),
+            },
+            past_addresses: vec![],
+            start: 0,
+            end: 1,
+        }
+    }
+
+    // Test that a valid entity passes
+    #[test]
+    fn test_valid_entity() {
+        assert!(valid_entity().validate().is_ok());
+    }
+
+    // Test that field names are reported in camelCase, like the JSON the client sent
+    #[test]
+    fn test_fields_are_camel_case() {
+        let entity = Entity {
+            first_name: String::new(),
+            challenge_1: 6,
+            ..valid_entity()
+        };
+        let errors = field_errors(&entity.validate().unwrap_err());
+        let fields: Vec<&str> = errors.iter().map(|error| error.field.as_str()).collect();
+        assert_eq!(fields, vec!["challenge1", "firstName"]);
+        assert_eq!(errors[0].code, "range");
+        assert_eq!(errors[1].code, "length");
+    }
+
+    // Test that nested structs and lists are flattened into paths
+    #[test]
+    fn test_nested_errors_are_flattened() {
+        let entity = Entity {
+            home_address: Address {
+                postal_code: String::new(),
+            },
+            past_addresses: vec![
+                Address {
+                    postal_code: "16802".to_owned(),
+                },
+                Address {
+                    postal_code: String::new(),
+                },
+            ],
+            ..valid_entity()
+        };
+        let errors = field_errors(&entity.validate().unwrap_err());
+        let fields: Vec<&str> = errors.iter().map(|error| error.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec!["homeAddress.postalCode", "pastAddresses[1].postalCode"]
+        );
+    }
+
+    // Test that schema errors are reported against the field they name
+    #[test]
+    fn test_schema_errors_use_named_field() {
+        let entity = Entity {
+            start: 2,
+            end: 1,
+            ..valid_entity()
+        };
+        let errors = field_errors(&entity.validate().unwrap_err());
+        assert_eq!(
+            errors,
+            vec![FieldError {
+                field: "endTime".to_owned(),
+                code: "end_before_start".to_owned(),
+                message: Some("endTime must be after startTime".to_owned()),
+            }]
+        );
+    }
+
+    // Test that database-backed checks produce a 422 for the given field
+    #[test]
+    fn test_invalid_field() {
+        let error = invalid_field("hackathonId", "not_found", "No hackathon with this id");
+        assert_eq!(error.status_code().as_u16(), 422);
+        match error {
+            ApiError::Validation(errors) => {
+                assert_eq!(errors.len(), 1);
+                assert_eq!(errors[0].field, "hackathonId");
+                assert_eq!(errors[0].code, "not_found");
+            }
+            other => panic!("expected a validation error, got {:?}", other.code()),
+        }
+    }
+
+    // Test that ranges must end after they start
+    #[test]
+    fn test_validate_time_range() {
+        let start = DateTime::parse_from_rfc3339("2024-02-10T09:00:00-05:00").unwrap();
+        let end = DateTime::parse_from_rfc3339("2024-02-11T17:00:00-05:00").unwrap();
+        assert!(validate_time_range(&start, &end).is_ok());
+        assert!(validate_time_range(&end, &start).is_err());
+        assert!(validate_time_range(&start, &start).is_err());
+    }
+
+    // Test that consents must be given
+    #[test]
+    fn test_must_be_true() {
+        assert!(must_be_true(&true).is_ok());
+        assert!(must_be_true(&false).is_err());
+    }
+
+    // Test that phone numbers allow common formatting but not letters
+    #[test]
+    fn test_phone_validator() {
+        assert!(PHONE_VALIDATOR.is_match("+1 (814) 555-0123"));
+        assert!(PHONE_VALIDATOR.is_match("814.555.0123"));
+        assert!(!PHONE_VALIDATOR.is_match("call me"));
+        assert!(!PHONE_VALIDATOR.is_match("123"));
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Update {
+        #[serde(default, deserialize_with = "nullable")]
+        allergies: Option<Option<String>>,
+    }
+
+    // Test that a nullable field left out, set to null and set to a value can be told apart
+    #[test]
+    fn test_nullable() {
+        let update = |json| serde_json::from_value::<Update>(json).unwrap().allergies;
+
+        assert_eq!(update(serde_json::json!({})), None);
+        assert_eq!(update(serde_json::json!({ "allergies": null })), Some(None));
+        assert_eq!(
+            update(serde_json::json!({ "allergies": "peanuts" })),
+            Some(Some("peanuts".to_owned()))
+        );
+    }
+}